@@ -0,0 +1,318 @@
+use std::{collections::HashMap, fmt::Write};
+
+use anyhow::Context as _;
+use poise::{
+    command,
+    serenity_prelude::{Mentionable, Timestamp, colours::branding::BLURPLE},
+};
+
+use crate::{
+    Context, ErrorHandler, Incident, IncidentStore, UserContext, Verbosity,
+    message::{ErrorMessage, deliver},
+    verbosity,
+};
+
+/// Default number of incidents listed by `/errors recent`.
+const DEFAULT_RECENT: usize = 10;
+
+/// Maximum number of incidents listed by `/errors recent`.
+const MAX_RECENT: usize = 25;
+
+/// Number of recent incidents `/errors stats` is calculated from.
+const STATS_SAMPLE: usize = 1000;
+
+/// Number of commands and fingerprints listed by `/errors stats`.
+const STATS_TOP: usize = 5;
+
+/// Maximum length of an error message in a list.
+const MAX_SUMMARY_LENGTH: usize = 100;
+
+/// Returns owner-only commands for inspecting handled errors and changing how
+/// they are handled: `/errors recent`, `/errors show <id>`, `/errors stats` and
+/// `/errors verbosity [level]`.
+///
+/// Incidents are read from the installed handler's incident store (see
+/// [`ErrorHandler::incident_store`]).
+///
+/// # Examples
+///
+/// ```
+/// use poise_error::{admin_commands, on_error};
+///
+/// let mut commands = vec![/* your commands */];
+///
+/// commands.extend(admin_commands());
+///
+/// let framework = poise::Framework::builder()
+///     .options(poise::FrameworkOptions {
+///         commands,
+///         on_error,
+///         ..Default::default()
+///     })
+///     .setup(|ctx, _ready, framework| {
+///         Box::pin(async move { Ok(()) })
+///     })
+///     .build();
+/// ```
+pub fn admin_commands<U: Send + Sync + 'static>() -> Vec<poise::Command<U, anyhow::Error>> {
+    vec![errors()]
+}
+
+/// Returns the installed handler's incident store, failing with a user error
+/// if there is none.
+fn incident_store() -> Result<&'static dyn IncidentStore, anyhow::Error> {
+    Ok(ErrorHandler::get().incidents().user_context(
+        "Incidents are not being recorded, configure an incident store to record them",
+    )?)
+}
+
+/// Formats `timestamp` as a Discord timestamp in `style`.
+fn discord_timestamp(timestamp: Timestamp, style: char) -> String {
+    format!("<t:{}:{style}>", timestamp.unix_timestamp())
+}
+
+/// Returns the outermost message of `incident`'s error, shortened for a list.
+fn summary(incident: &Incident) -> String {
+    let message = incident
+        .chain
+        .first()
+        .map(String::as_str)
+        .unwrap_or_default();
+
+    if message.chars().count() > MAX_SUMMARY_LENGTH {
+        format!(
+            "{}…",
+            message
+                .chars()
+                .take(MAX_SUMMARY_LENGTH - 1)
+                .collect::<String>(),
+        )
+    } else {
+        message.to_string()
+    }
+}
+
+/// Inspects handled errors
+#[command(
+    slash_command,
+    prefix_command,
+    owners_only,
+    default_member_permissions = "ADMINISTRATOR",
+    subcommands("recent", "show", "stats", "verbosity"),
+    subcommand_required
+)]
+async fn errors<U: Send + Sync + 'static>(_ctx: Context<'_, U>) -> Result<(), anyhow::Error> {
+    Ok(())
+}
+
+/// Lists the most recent incidents
+#[command(slash_command, prefix_command, owners_only)]
+async fn recent<U: Send + Sync + 'static>(
+    ctx: Context<'_, U>,
+    #[description = "Only list incidents from this command"] command: Option<String>,
+    #[description = "How many incidents to list"]
+    #[min = 1]
+    #[max = 25]
+    count: Option<usize>,
+) -> Result<(), anyhow::Error> {
+    let store = incident_store()?;
+    let count = count.unwrap_or(DEFAULT_RECENT).clamp(1, MAX_RECENT);
+    let incidents = match &command {
+        Some(command) => store.by_command(command, count),
+        None => store.recent(count),
+    }
+    .context("Failed to read incidents")?;
+    let description = if incidents.is_empty() {
+        "No incidents have been recorded.".to_string()
+    } else {
+        incidents
+            .iter()
+            .map(|incident| {
+                format!(
+                    "- `{}` {} `{}`: {}",
+                    incident.id,
+                    discord_timestamp(incident.timestamp, 'R'),
+                    incident.command.as_deref().unwrap_or("no command"),
+                    summary(incident),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    deliver(
+        ctx,
+        &ErrorMessage::new()
+            .title(match command {
+                Some(command) => format!("Recent incidents in `{command}`"),
+                None => "Recent incidents".to_string(),
+            })
+            .description(description)
+            .color(BLURPLE),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Shows the details of an incident
+#[command(slash_command, prefix_command, owners_only)]
+async fn show<U: Send + Sync + 'static>(
+    ctx: Context<'_, U>,
+    #[description = "ID of the incident"] id: String,
+) -> Result<(), anyhow::Error> {
+    let incident = incident_store()?
+        .get(id.trim())
+        .context("Failed to read incidents")?
+        .with_user_context(|| format!("There is no incident with the ID `{id}`"))?;
+    let mut details = verbosity::format_chain(incident.chain.iter().cloned());
+
+    if let Some(backtrace) = &incident.backtrace {
+        write!(details, "\n\nStack backtrace:\n{backtrace}").unwrap();
+    }
+
+    let mut message = ErrorMessage::new()
+        .title(format!("Incident `{}`", incident.id))
+        .details(details)
+        .color(BLURPLE)
+        .field("When", discord_timestamp(incident.timestamp, 'F'), true)
+        .field(
+            "Command",
+            match &incident.command {
+                Some(command) => format!("`{command}`"),
+                None => "None".to_string(),
+            },
+            true,
+        );
+
+    if let Some(user_id) = incident.user_id {
+        message = message.field("User", user_id.mention().to_string(), true);
+    }
+
+    if let Some(channel_id) = incident.channel_id {
+        message = message.field("Channel", channel_id.mention().to_string(), true);
+    }
+
+    if let Some(guild_id) = incident.guild_id {
+        message = message.field("Guild", format!("`{guild_id}`"), true);
+    }
+
+    if let Some(invocation) = &incident.invocation {
+        message = message.field("Invocation", format!("`{invocation}`"), false);
+    }
+
+    deliver(
+        ctx,
+        &message.footer(format!("Fingerprint: {}", incident.fingerprint)),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Shows which commands and errors incidents come from most
+#[command(slash_command, prefix_command, owners_only)]
+async fn stats<U: Send + Sync + 'static>(ctx: Context<'_, U>) -> Result<(), anyhow::Error> {
+    let incidents = incident_store()?
+        .recent(STATS_SAMPLE)
+        .context("Failed to read incidents")?;
+    let day_ago = Timestamp::now().unix_timestamp() - 24 * 60 * 60;
+    let last_day = incidents
+        .iter()
+        .filter(|incident| incident.timestamp.unix_timestamp() >= day_ago)
+        .count();
+    let mut commands: HashMap<&str, usize> = HashMap::new();
+    let mut fingerprints: HashMap<&str, (usize, &Incident)> = HashMap::new();
+
+    for incident in &incidents {
+        *commands
+            .entry(incident.command.as_deref().unwrap_or("no command"))
+            .or_default() += 1;
+        fingerprints
+            .entry(&incident.fingerprint)
+            .or_insert((0, incident))
+            .0 += 1;
+    }
+
+    let mut commands: Vec<_> = commands.into_iter().collect();
+    let mut fingerprints: Vec<_> = fingerprints.into_values().collect();
+
+    commands.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    fingerprints.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.timestamp.cmp(&a.1.timestamp)));
+
+    let mut message = ErrorMessage::new()
+        .title("Incident statistics")
+        .description(format!(
+            "{} incidents in the last 24 hours, out of the {} most recent incidents.",
+            last_day,
+            incidents.len(),
+        ))
+        .color(BLURPLE);
+
+    if !commands.is_empty() {
+        message = message.field(
+            "Commands",
+            commands
+                .iter()
+                .take(STATS_TOP)
+                .map(|(command, count)| format!("- `{command}`: {count}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            false,
+        );
+    }
+
+    if !fingerprints.is_empty() {
+        message = message.field(
+            "Errors",
+            fingerprints
+                .iter()
+                .take(STATS_TOP)
+                .map(|(count, incident)| {
+                    format!(
+                        "- {count}× (latest `{}`): {}",
+                        incident.id,
+                        summary(incident)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            false,
+        );
+    }
+
+    deliver(ctx, &message).await;
+
+    Ok(())
+}
+
+/// Shows or changes how much detail about errors is shown to users
+#[command(slash_command, prefix_command, owners_only)]
+async fn verbosity<U: Send + Sync + 'static>(
+    ctx: Context<'_, U>,
+    #[description = "How much detail to show"] level: Option<Verbosity>,
+) -> Result<(), anyhow::Error> {
+    let handler = ErrorHandler::get();
+    let description = match level {
+        Some(level) => {
+            handler.set_verbosity(level);
+
+            format!("Verbosity is now `{level:?}`.")
+        }
+        None => format!(
+            "Verbosity is currently `{:?}`.",
+            handler.current_verbosity(),
+        ),
+    };
+
+    deliver(
+        ctx,
+        &ErrorMessage::new()
+            .title("Verbosity")
+            .description(description)
+            .color(BLURPLE),
+    )
+    .await;
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::{collections::BTreeMap, error::Error as StdError};
+
+/// Explanations of the built-in error codes.
+const BUILT_IN: &[(&str, &str)] = &[
+    (
+        "PE-USER",
+        "The command could not do what was asked because of how it was used. The message explains what went wrong, fix it and try again.",
+    ),
+    (
+        "PE-NOTICE",
+        "The command stopped early without anything going wrong, such as when there is nothing for it to do. The message explains why.",
+    ),
+    (
+        "PE-INTERNAL",
+        "The command failed because of a problem on the bot's end. Trying again may work, otherwise give a developer the incident ID, if any, so that they can find out what happened.",
+    ),
+    (
+        "PE-SUBCOMMAND-REQUIRED",
+        "This command groups other commands and cannot be used on its own. Use one of the subcommands listed in the message instead.",
+    ),
+    (
+        "PE-PANIC",
+        "The bot crashed while running the command. This is always a bug, give a developer the incident ID, if any, so that they can fix it.",
+    ),
+    (
+        "PE-ARGUMENT",
+        "One of the arguments given to the command could not be understood, such as text where a number was expected. Check the command's usage and try again.",
+    ),
+    (
+        "PE-COMMAND-MISMATCH",
+        "The command has changed since Discord last learned about it, so Discord sent the bot arguments it does not expect. This fixes itself once the bot registers its commands again.",
+    ),
+    (
+        "PE-COOLDOWN",
+        "The command was used too recently. Wait for the time shown in the message before using it again.",
+    ),
+    (
+        "PE-BOT-PERMISSIONS",
+        "The bot is missing permissions it needs to run the command in this server or channel. Ask a server administrator to grant the permissions listed in the message.",
+    ),
+    (
+        "PE-USER-PERMISSIONS",
+        "You are missing permissions needed to run the command in this server or channel. Ask a server administrator if you believe you should have them.",
+    ),
+    (
+        "PE-OWNER-ONLY",
+        "The command can only be used by the bot's owners.",
+    ),
+    (
+        "PE-GUILD-ONLY",
+        "The command can only be used in a server, not in DMs.",
+    ),
+    (
+        "PE-DM-ONLY",
+        "The command can only be used in DMs with the bot.",
+    ),
+    (
+        "PE-NSFW-ONLY",
+        "The command can only be used in channels marked as NSFW.",
+    ),
+    (
+        "PE-CHECK",
+        "The bot could not decide whether you are allowed to use the command because of a problem on its end. Try again later, or give a developer the incident ID, if any.",
+    ),
+    (
+        "PE-UNKNOWN-COMMAND",
+        "The message started with the bot's prefix, but no command has that name. Check the suggested commands for a typo.",
+    ),
+    (
+        "PE-UNKNOWN-INTERACTION",
+        "Discord sent the bot a command it no longer has, usually because the command was removed or renamed. It disappears once Discord catches up.",
+    ),
+    (
+        "PE-SETUP",
+        "The bot failed to start up. Only developers see this code, in the bot's logs.",
+    ),
+    (
+        "PE-EVENT",
+        "The bot failed to handle an event from Discord. Only developers see this code, in the bot's logs.",
+    ),
+    (
+        "PE-PREFIX",
+        "The bot failed to work out which prefix to use for a message. Only developers see this code, in the bot's logs.",
+    ),
+];
+
+/// Returns whether an error is of a registered type.
+type Matcher = fn(&(dyn StdError + 'static)) -> bool;
+
+/// Stable codes for errors and long-form explanations of them, which are shown
+/// by `/explain` (see [`explain_commands`][crate::explain_commands]).
+///
+/// Each kind of error [`poise_error`][crate] handles has a built-in code
+/// starting with `PE-`, such as `PE-COOLDOWN` (see
+/// [`ErrorClass::code`][crate::ErrorClass::code]). Your own error types can be
+/// registered with codes of their own, which are used when they appear
+/// anywhere in an error's chain. Codes are shown in the footer of messages
+/// about errors and are looked up case-insensitively.
+///
+/// # Examples
+///
+/// ```
+/// use poise_error::{ErrorCatalog, ErrorHandler};
+/// use thiserror::Error;
+///
+/// #[derive(Error, Debug)]
+/// #[error("The shop is closed")]
+/// struct ShopClosed;
+///
+/// let catalog = ErrorCatalog::new().register::<ShopClosed>(
+///     "APP-0042",
+///     "The shop is only open on weekends, come back on Saturday.",
+/// );
+///
+/// assert!(catalog.get("app-0042").is_some());
+/// assert!(catalog.get("PE-COOLDOWN").is_some());
+///
+/// ErrorHandler::new().catalog(catalog);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ErrorCatalog {
+    explanations: BTreeMap<String, String>,
+    types: Vec<(&'static str, Matcher)>,
+}
+
+impl ErrorCatalog {
+    /// Creates a catalog of the built-in error codes.
+    pub fn new() -> Self {
+        Self {
+            explanations: BUILT_IN
+                .iter()
+                .map(|(code, explanation)| (code.to_string(), explanation.to_string()))
+                .collect(),
+            types: Vec::new(),
+        }
+    }
+
+    /// Adds or replaces the explanation of `code`.
+    pub fn explain(mut self, code: &str, explanation: impl Into<String>) -> Self {
+        self.explanations
+            .insert(code.to_uppercase(), explanation.into());
+        self
+    }
+
+    /// Gives errors of type `E` the code `code`, explained by `explanation`.
+    ///
+    /// If an error's chain contains several registered types, the outermost
+    /// one's code is used.
+    pub fn register<E: StdError + Send + Sync + 'static>(
+        mut self,
+        code: &'static str,
+        explanation: impl Into<String>,
+    ) -> Self {
+        self.types.push((code, |error| error.is::<E>()));
+        self.explain(code, explanation)
+    }
+
+    /// Returns the explanation of `code`, if it is in the catalog.
+    pub fn get(&self, code: &str) -> Option<&str> {
+        self.explanations
+            .get(&code.trim().to_uppercase())
+            .map(String::as_str)
+    }
+
+    /// Returns every code in the catalog, in alphabetical order.
+    pub fn codes(&self) -> impl Iterator<Item = &str> {
+        self.explanations.keys().map(String::as_str)
+    }
+
+    /// Returns the code of the outermost registered error type in `error`'s
+    /// chain, if any.
+    pub(crate) fn code_of(&self, error: &anyhow::Error) -> Option<&'static str> {
+        error.chain().find_map(|cause| {
+            self.types
+                .iter()
+                .find(|(_, is)| is(cause))
+                .map(|(code, _)| *code)
+        })
+    }
+}
+
+impl Default for ErrorCatalog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
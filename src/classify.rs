@@ -0,0 +1,315 @@
+use std::error::Error as StdError;
+
+use poise::{
+    FrameworkError,
+    serenity_prelude::{
+        Colour,
+        colours::{
+            css::{DANGER, WARNING},
+            roles::DEFAULT,
+        },
+    },
+};
+
+use crate::{ErrorHandler, Notice, UserError};
+
+/// Whose fault an error is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    /// The user made a mistake, such as passing an invalid argument or
+    /// returning a [`UserError`], or a command returned a [`Notice`].
+    User,
+    /// The user or bot is not allowed to use a command, or the command cannot
+    /// be used where it was invoked.
+    Permission,
+    /// The bot is out of sync with Discord, such as when commands have changed
+    /// since they were registered.
+    Environment,
+    /// The bot has a bug.
+    Internal,
+    /// Nothing went wrong which is worth replying to, such as a message which
+    /// looks like a command but is not one. These errors are only logged,
+    /// unless similar commands are suggested (see
+    /// [`ErrorHandler::suggest_unknown_commands`][crate::ErrorHandler::suggest_unknown_commands]).
+    Ignored,
+}
+
+/// How serious an error is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// Not an error, only something the user should know, which is not logged.
+    Info,
+    /// Something the user can fix, logged as a warning.
+    Warning,
+    /// Something a developer should fix, logged as an error.
+    Error,
+}
+
+/// What kind of error [`poise`] has given, as determined by [`classify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ErrorClass {
+    /// Whose fault the error is.
+    pub category: Category,
+    /// How serious the error is.
+    pub severity: Severity,
+    /// A stable code identifying the error, such as `PE-COOLDOWN`, or the code
+    /// of a custom error type registered in the handler's catalog (see
+    /// [`ErrorCatalog`][crate::ErrorCatalog]).
+    pub code: &'static str,
+}
+
+impl ErrorClass {
+    const fn new(category: Category, severity: Severity, code: &'static str) -> Self {
+        Self {
+            category,
+            severity,
+            code,
+        }
+    }
+
+    /// Returns the colour of messages about the error.
+    pub fn colour(&self) -> Colour {
+        match self.severity {
+            Severity::Info => DEFAULT,
+            Severity::Warning => WARNING,
+            Severity::Error => DANGER,
+        }
+    }
+}
+
+/// Classifies `error` the same way
+/// [`try_handle_error`][crate::try_handle_error] does, so that custom handlers,
+/// metrics and reporters can agree with it.
+///
+/// A command's error is a user error if a [`UserError`] or [`Notice`] appears
+/// anywhere in its chain (see [`UserError`] for details).
+///
+/// # Examples
+///
+/// ```
+/// use poise::FrameworkError;
+/// use poise_error::{Category, classify, try_handle_error};
+/// use tracing::info;
+///
+/// async fn my_custom_error_handler<U: 'static>(
+///     error: FrameworkError<'_, U, anyhow::Error>,
+/// ) -> Result<(), anyhow::Error> {
+///     let class = classify(&error);
+///
+///     if class.category == Category::Internal {
+///         info!("Counting an internal error with {:?} severity", class.severity);
+///     }
+///
+///     try_handle_error(error).await
+/// }
+/// ```
+pub fn classify<U>(error: &FrameworkError<'_, U, anyhow::Error>) -> ErrorClass {
+    use Category::*;
+    use Severity::*;
+
+    let class = match error {
+        FrameworkError::Command { error, .. } => classify_command_error(error),
+        FrameworkError::SubcommandRequired { .. } => {
+            ErrorClass::new(User, Warning, "PE-SUBCOMMAND-REQUIRED")
+        }
+        FrameworkError::CommandPanic { .. } => ErrorClass::new(Internal, Error, "PE-PANIC"),
+        FrameworkError::ArgumentParse { .. } => ErrorClass::new(User, Warning, "PE-ARGUMENT"),
+        FrameworkError::CommandStructureMismatch { .. } => {
+            ErrorClass::new(Environment, Error, "PE-COMMAND-MISMATCH")
+        }
+        FrameworkError::CooldownHit { .. } => ErrorClass::new(User, Warning, "PE-COOLDOWN"),
+        FrameworkError::MissingBotPermissions { .. } => {
+            ErrorClass::new(Permission, Warning, "PE-BOT-PERMISSIONS")
+        }
+        FrameworkError::MissingUserPermissions { .. } => {
+            ErrorClass::new(Permission, Warning, "PE-USER-PERMISSIONS")
+        }
+        FrameworkError::NotAnOwner { .. } => ErrorClass::new(Permission, Warning, "PE-OWNER-ONLY"),
+        FrameworkError::GuildOnly { .. } => ErrorClass::new(Permission, Warning, "PE-GUILD-ONLY"),
+        FrameworkError::DmOnly { .. } => ErrorClass::new(Permission, Warning, "PE-DM-ONLY"),
+        FrameworkError::NsfwOnly { .. } => ErrorClass::new(Permission, Warning, "PE-NSFW-ONLY"),
+        FrameworkError::CommandCheckFailed { error: Some(_), .. } => {
+            ErrorClass::new(Internal, Error, "PE-CHECK")
+        }
+        FrameworkError::CommandCheckFailed { error: None, .. } => {
+            ErrorClass::new(Ignored, Warning, "PE-CHECK")
+        }
+        FrameworkError::UnknownCommand { .. } => {
+            ErrorClass::new(Ignored, Warning, "PE-UNKNOWN-COMMAND")
+        }
+        FrameworkError::UnknownInteraction { .. } => {
+            ErrorClass::new(Environment, Warning, "PE-UNKNOWN-INTERACTION")
+        }
+        FrameworkError::Setup { .. } => ErrorClass::new(Internal, Error, "PE-SETUP"),
+        FrameworkError::EventHandler { .. } => ErrorClass::new(Internal, Error, "PE-EVENT"),
+        FrameworkError::DynamicPrefix { .. } => ErrorClass::new(Internal, Error, "PE-PREFIX"),
+        _ => ErrorClass::new(Internal, Error, "PE-INTERNAL"),
+    };
+    let custom_code = match error {
+        FrameworkError::CommandCheckFailed {
+            error: Some(error), ..
+        }
+        | FrameworkError::Setup { error, .. }
+        | FrameworkError::EventHandler { error, .. } => ErrorHandler::get().catalog.code_of(error),
+        _ => None,
+    };
+
+    ErrorClass {
+        code: custom_code.unwrap_or(class.code),
+        ..class
+    }
+}
+
+/// Classifies `error`, returned while running a command.
+pub(crate) fn classify_command_error(error: &anyhow::Error) -> ErrorClass {
+    use Category::*;
+    use Severity::*;
+
+    let class = match user_severity(error) {
+        Some(Info) => ErrorClass::new(User, Info, "PE-NOTICE"),
+        Some(_) => ErrorClass::new(User, Warning, "PE-USER"),
+        None => ErrorClass::new(Internal, Error, "PE-INTERNAL"),
+    };
+
+    ErrorClass {
+        code: ErrorHandler::get()
+            .catalog
+            .code_of(error)
+            .unwrap_or(class.code),
+        ..class
+    }
+}
+
+/// Finds the outermost [`UserError`] or [`Notice`] in `error`'s chain,
+/// including the sources of other error types wrapping it, and returns its
+/// severity.
+fn user_severity(error: &anyhow::Error) -> Option<Severity> {
+    match (position::<UserError>(error), position::<Notice>(error)) {
+        (Some(user_error), Some(notice)) if notice < user_error => Some(Severity::Info),
+        (Some(_), _) => Some(Severity::Warning),
+        (None, Some(_)) => Some(Severity::Info),
+        (None, None) => None,
+    }
+}
+
+/// Returns the position of the outermost error of type `T` in `error`'s chain.
+fn position<T>(error: &anyhow::Error) -> Option<usize>
+where
+    T: StdError + Send + Sync + 'static,
+{
+    // Errors attached with `anyhow::Context::context` are wrapped in a type
+    // private to `anyhow`, so they can only be found with `downcast_ref`, and
+    // are then located in the chain by which link contains them.
+    let attached = error
+        .downcast_ref::<T>()
+        .map(|found| found as *const T as usize);
+
+    error.chain().position(|cause| {
+        cause.is::<T>()
+            || attached.is_some_and(|address| {
+                let start = cause as *const dyn StdError as *const u8 as usize;
+
+                (start..start + size_of_val(cause)).contains(&address)
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{Context as _, anyhow};
+    use thiserror::Error;
+
+    use super::*;
+
+    #[derive(Error, Debug)]
+    #[error("failed to buy item")]
+    struct BuyError(#[source] anyhow::Error);
+
+    #[test]
+    fn internal_errors_are_not_user_errors() {
+        let error = anyhow!("database is down").context("failed to buy item");
+
+        assert_eq!(user_severity(&error), None);
+        assert_eq!(
+            classify_command_error(&error),
+            ErrorClass::new(Category::Internal, Severity::Error, "PE-INTERNAL"),
+        );
+    }
+
+    #[test]
+    fn user_errors_are_found_under_context() {
+        let error = anyhow::Error::from(UserError(anyhow!("You have no coins")))
+            .context("failed to buy item")
+            .context("failed to run command");
+
+        assert_eq!(user_severity(&error), Some(Severity::Warning));
+        assert_eq!(
+            classify_command_error(&error),
+            ErrorClass::new(Category::User, Severity::Warning, "PE-USER"),
+        );
+    }
+
+    #[test]
+    fn user_errors_are_found_through_sources() {
+        let error = anyhow::Error::from(BuyError(UserError(anyhow!("You have no coins")).into()));
+
+        assert_eq!(user_severity(&error), Some(Severity::Warning));
+    }
+
+    #[test]
+    fn user_errors_win_over_internal_errors() {
+        let error = anyhow::Error::from(UserError(anyhow!("database is down")))
+            .context("You cannot buy this right now");
+
+        assert_eq!(user_severity(&error), Some(Severity::Warning));
+
+        let error = Err::<(), _>(anyhow!("database is down"))
+            .context(UserError(anyhow!("You cannot buy this right now")))
+            .unwrap_err();
+
+        assert_eq!(user_severity(&error), Some(Severity::Warning));
+    }
+
+    #[test]
+    fn notices_are_found_under_context() {
+        let error = anyhow::Error::from(Notice(anyhow!("You have no items yet")))
+            .context("failed to list items");
+
+        assert_eq!(user_severity(&error), Some(Severity::Info));
+        assert_eq!(
+            classify_command_error(&error),
+            ErrorClass::new(Category::User, Severity::Info, "PE-NOTICE"),
+        );
+    }
+
+    #[test]
+    fn outermost_of_user_error_and_notice_decides() {
+        let error = anyhow::Error::from(Notice(anyhow!("Nothing to do")))
+            .context(UserError(anyhow!("You have no items")));
+
+        assert_eq!(user_severity(&error), Some(Severity::Warning));
+
+        let error = anyhow::Error::from(UserError(anyhow!("You have no items")))
+            .context(Notice(anyhow!("Nothing to do")));
+
+        assert_eq!(user_severity(&error), Some(Severity::Info));
+
+        let error = anyhow::Error::from(BuyError(
+            Notice(UserError(anyhow!("You have no items")).into()).into(),
+        ));
+
+        assert_eq!(user_severity(&error), Some(Severity::Info));
+
+        let error = anyhow!("out of stock")
+            .context(Notice(anyhow!("Nothing to do")))
+            .context(UserError(anyhow!("You have no items")));
+
+        assert_eq!(user_severity(&error), Some(Severity::Warning));
+
+        let error = anyhow!("out of stock")
+            .context(UserError(anyhow!("You have no items")))
+            .context(Notice(anyhow!("Nothing to do")));
+
+        assert_eq!(user_severity(&error), Some(Severity::Info));
+    }
+}
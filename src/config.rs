@@ -0,0 +1,431 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use anyhow::{Context as _, bail};
+use poise::serenity_prelude::Colour;
+use serde::{Deserialize, Serialize};
+
+use crate::template::{self, Placeholder};
+
+/// Who sees a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// Only the user who invoked the command sees the message. Only affects
+    /// slash commands; prefix commands are always replied to publicly.
+    Ephemeral,
+    /// Everyone in the channel sees the message.
+    Public,
+    /// No message is sent, the error is only logged.
+    Silent,
+}
+
+/// A colour written as a hex code, such as `"#E74C3C"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct HexColour(pub Colour);
+
+impl FromStr for HexColour {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+
+        // `from_str_radix` would also accept a leading `+`.
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("Expected a colour like \"#E74C3C\", found {s:?}");
+        }
+
+        Ok(Self(Colour::new(u32::from_str_radix(hex, 16)?)))
+    }
+}
+
+impl TryFrom<String> for HexColour {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<HexColour> for String {
+    fn from(value: HexColour) -> Self {
+        value.to_string()
+    }
+}
+
+impl Display for HexColour {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:06X}", self.0.0)
+    }
+}
+
+/// Overrides for one kind of message. Anything left unset keeps its built-in
+/// value.
+///
+/// The title, description and footer may contain placeholders (see
+/// [`Messages`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MessageConfig {
+    /// The message's title.
+    pub title: Option<String>,
+    /// The message's description, shown above any details.
+    pub description: Option<String>,
+    /// The message's footer.
+    pub footer: Option<String>,
+    /// The colour of the message's embed or container.
+    pub colour: Option<HexColour>,
+    /// Who sees the message.
+    pub visibility: Option<Visibility>,
+}
+
+/// The wording and styling of every message [`poise_error`][crate] sends,
+/// usually loaded from a TOML or JSON file (see [`MessagesHandle`]).
+///
+/// Each field corresponds to a kind of error. Messages which are not
+/// configured keep their built-in wording and styling. Unknown fields are
+/// rejected, so that typos are caught when the file is loaded.
+///
+/// # Placeholders
+///
+/// Titles, descriptions and footers may contain placeholders, which are
+/// replaced with details of the error. Every message supports:
+///
+/// - `{command}`: the name of the command, including its parent commands.
+/// - `{prefix}`: the prefix the command was invoked with, `/` for slash
+///   commands.
+/// - `{user}`: a mention of the user who invoked the command.
+///
+/// Some messages support more, as noted on their fields:
+///
+/// - `{remaining}`: the number of seconds until a cooldown ends.
+/// - `{permissions}`: the missing permissions.
+/// - `{subcommands}`: a list of the subcommands which can be invoked, with
+///   their descriptions.
+/// - `{incident_id}`: the ID of the recorded incident, if any (see
+///   [`ErrorHandler::incident_store`][crate::ErrorHandler::incident_store]).
+///
+/// Write `{{` and `}}` for literal braces. Unknown placeholders, and
+/// placeholders a message does not support, are rejected when the messages are
+/// loaded.
+///
+/// # Examples
+///
+/// ```
+/// use poise_error::Messages;
+///
+/// let messages = Messages::from_toml(
+///     r##"
+///     [internal_error]
+///     title = "Oops!"
+///     footer = "Our team has been notified."
+///     colour = "#E67E22"
+///
+///     [cooldown_hit]
+///     description = "Slow down {user}, try `{prefix}{command}` again in {remaining} seconds."
+///     visibility = "public"
+///     "##,
+/// )
+/// .unwrap();
+///
+/// assert!(Messages::from_toml("[unknown_section]\ntitle = \"Unknown\"").is_err());
+/// assert!(Messages::from_toml("[guild_only]\ntitle = \"Wait {remaining}s\"").is_err());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Messages {
+    /// Sent when a command returns a [`UserError`][crate::UserError].
+    pub user_error: MessageConfig,
+    /// Sent when a command returns a [`Notice`][crate::Notice].
+    pub notice: MessageConfig,
+    /// Sent when a command returns any other error. Supports `{incident_id}`.
+    pub internal_error: MessageConfig,
+    /// Sent when a command is invoked without a required subcommand. Supports
+    /// `{subcommands}`.
+    pub subcommand_required: MessageConfig,
+    /// Sent when a command panics. Supports `{incident_id}`.
+    pub panic: MessageConfig,
+    /// Sent when an argument cannot be parsed.
+    pub argument_parse: MessageConfig,
+    /// Sent when a command's registered structure does not match its
+    /// definition.
+    pub command_structure_mismatch: MessageConfig,
+    /// Sent when a command's registered structure does not match its definition
+    /// and commands are being registered again (see
+    /// [`ErrorHandler::reregister_mismatched_commands`][crate::ErrorHandler::reregister_mismatched_commands]).
+    pub command_being_updated: MessageConfig,
+    /// Sent when a command is on cooldown. Supports `{remaining}`.
+    pub cooldown_hit: MessageConfig,
+    /// Sent when the bot lacks permissions required by a command. Supports
+    /// `{permissions}`.
+    pub missing_bot_permissions: MessageConfig,
+    /// Sent when the user lacks permissions required by a command. Supports
+    /// `{permissions}`, which is empty if the missing permissions are unknown.
+    pub missing_user_permissions: MessageConfig,
+    /// Sent when someone other than an owner invokes an owner-only command.
+    pub not_an_owner: MessageConfig,
+    /// Sent when a server-only command is invoked outside of a server.
+    pub guild_only: MessageConfig,
+    /// Sent when a DMs-only command is invoked outside of DMs.
+    pub dm_only: MessageConfig,
+    /// Sent when an NSFW command is invoked outside of an NSFW channel.
+    pub nsfw_only: MessageConfig,
+    /// Sent when a command's check returns an error. Supports `{incident_id}`.
+    pub check_failed: MessageConfig,
+    /// Sent when a prefix message is similar to a command (see
+    /// [`ErrorHandler::suggest_unknown_commands`][crate::ErrorHandler::suggest_unknown_commands]).
+    pub unknown_command: MessageConfig,
+    /// Sent when an interaction for an unknown command is received.
+    pub unknown_interaction: MessageConfig,
+}
+
+impl Messages {
+    /// Parses and validates messages from TOML.
+    pub fn from_toml(toml: &str) -> Result<Self, anyhow::Error> {
+        let messages: Self = toml::from_str(toml)?;
+
+        messages.validate()?;
+
+        Ok(messages)
+    }
+
+    /// Parses and validates messages from JSON.
+    pub fn from_json(json: &str) -> Result<Self, anyhow::Error> {
+        let messages: Self = serde_json::from_str(json)?;
+
+        messages.validate()?;
+
+        Ok(messages)
+    }
+
+    /// Checks that every message only uses placeholders it supports.
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        const INCIDENT: &[Placeholder] = &[
+            Placeholder::Command,
+            Placeholder::Prefix,
+            Placeholder::User,
+            Placeholder::IncidentId,
+        ];
+        const PERMISSIONS: &[Placeholder] = &[
+            Placeholder::Command,
+            Placeholder::Prefix,
+            Placeholder::User,
+            Placeholder::Permissions,
+        ];
+
+        let messages: [(&str, &MessageConfig, &[Placeholder]); 18] = [
+            ("user_error", &self.user_error, Placeholder::CONTEXT),
+            ("notice", &self.notice, Placeholder::CONTEXT),
+            ("internal_error", &self.internal_error, INCIDENT),
+            (
+                "subcommand_required",
+                &self.subcommand_required,
+                &[
+                    Placeholder::Command,
+                    Placeholder::Prefix,
+                    Placeholder::User,
+                    Placeholder::Subcommands,
+                ],
+            ),
+            ("panic", &self.panic, INCIDENT),
+            ("argument_parse", &self.argument_parse, Placeholder::CONTEXT),
+            (
+                "command_structure_mismatch",
+                &self.command_structure_mismatch,
+                Placeholder::CONTEXT,
+            ),
+            (
+                "command_being_updated",
+                &self.command_being_updated,
+                Placeholder::CONTEXT,
+            ),
+            (
+                "cooldown_hit",
+                &self.cooldown_hit,
+                &[
+                    Placeholder::Command,
+                    Placeholder::Prefix,
+                    Placeholder::User,
+                    Placeholder::Remaining,
+                ],
+            ),
+            (
+                "missing_bot_permissions",
+                &self.missing_bot_permissions,
+                PERMISSIONS,
+            ),
+            (
+                "missing_user_permissions",
+                &self.missing_user_permissions,
+                PERMISSIONS,
+            ),
+            ("not_an_owner", &self.not_an_owner, Placeholder::CONTEXT),
+            ("guild_only", &self.guild_only, Placeholder::CONTEXT),
+            ("dm_only", &self.dm_only, Placeholder::CONTEXT),
+            ("nsfw_only", &self.nsfw_only, Placeholder::CONTEXT),
+            ("check_failed", &self.check_failed, INCIDENT),
+            (
+                "unknown_command",
+                &self.unknown_command,
+                Placeholder::CONTEXT,
+            ),
+            (
+                "unknown_interaction",
+                &self.unknown_interaction,
+                Placeholder::CONTEXT,
+            ),
+        ];
+
+        for (name, config, available) in messages {
+            let templates = [
+                ("title", &config.title),
+                ("description", &config.description),
+                ("footer", &config.footer),
+            ];
+
+            for (field, template) in templates {
+                if let Some(template) = template {
+                    template::validate(template, available)
+                        .with_context(|| format!("Invalid template in {name}.{field}"))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads messages from the file at `path`, which is parsed as JSON if its
+    /// extension is `.json` and as TOML otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let messages = if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            Self::from_json(&contents)
+        } else {
+            Self::from_toml(&contents)
+        };
+
+        messages.with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+/// A shared handle to the [`Messages`] used by an
+/// [`ErrorHandler`][crate::ErrorHandler], which can be reloaded while the bot
+/// is running.
+///
+/// Clones of a handle share the same messages, so keep a clone around to
+/// reload them later, such as from a command or when the file changes.
+///
+/// # Examples
+///
+/// ```no_run
+/// use poise_error::{ErrorHandler, MessagesHandle};
+///
+/// let messages = MessagesHandle::load("messages.toml").unwrap();
+///
+/// ErrorHandler::new()
+///     .messages(messages.clone())
+///     .install()
+///     .unwrap();
+///
+/// // Later, after editing messages.toml:
+/// if let Err(err) = messages.reload() {
+///     eprintln!("Kept the previous messages: {err:#}");
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MessagesHandle {
+    path: Option<PathBuf>,
+    messages: Arc<RwLock<Arc<Messages>>>,
+}
+
+impl MessagesHandle {
+    /// Creates a handle to `messages`, which cannot be reloaded from a file.
+    ///
+    /// Fails if the messages are invalid (see [`Messages::validate`]).
+    pub fn new(messages: Messages) -> Result<Self, anyhow::Error> {
+        messages.validate()?;
+
+        Ok(Self {
+            path: None,
+            messages: Arc::new(RwLock::new(Arc::new(messages))),
+        })
+    }
+
+    /// Creates a handle to the messages in the file at `path` (see
+    /// [`Messages::load`]), which can be reloaded with
+    /// [`MessagesHandle::reload`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            ..Self::new(Messages::load(path)?)?
+        })
+    }
+
+    /// Reads the messages from the file this handle was loaded from again.
+    ///
+    /// If the file cannot be read or is invalid, the previous messages are kept
+    /// and an error is returned.
+    pub fn reload(&self) -> Result<(), anyhow::Error> {
+        let Some(path) = &self.path else {
+            bail!("These messages were not loaded from a file");
+        };
+
+        self.set(Messages::load(path)?)
+    }
+
+    /// Replaces the messages.
+    ///
+    /// If the messages are invalid (see [`Messages::validate`]), the previous
+    /// messages are kept and an error is returned.
+    pub fn set(&self, messages: Messages) -> Result<(), anyhow::Error> {
+        messages.validate()?;
+        *self.messages.write().unwrap() = Arc::new(messages);
+
+        Ok(())
+    }
+
+    /// Returns the current messages.
+    pub fn get(&self) -> Arc<Messages> {
+        self.messages.read().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_colours_are_parsed() {
+        assert_eq!(
+            "#E74C3C".parse::<HexColour>().unwrap().0,
+            Colour::new(0xE74C3C)
+        );
+        assert_eq!(
+            "e74c3c".parse::<HexColour>().unwrap().0,
+            Colour::new(0xE74C3C)
+        );
+        assert_eq!(HexColour(Colour::new(0xABC)).to_string(), "#000ABC");
+    }
+
+    #[test]
+    fn malformed_hex_colours_are_rejected() {
+        for colour in [
+            "#+12345", "+123456", "#-12345", "#E74C3", "#E74C3CC", "#G74C3C", "#é4C3C",
+        ] {
+            assert!(colour.parse::<HexColour>().is_err(), "{colour:?}");
+        }
+    }
+}
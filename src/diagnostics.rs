@@ -0,0 +1,169 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use poise::{command, serenity_prelude::colours::branding::BLURPLE};
+use thiserror::Error;
+
+use crate::{
+    Context, Notice, UserContext,
+    message::{ErrorMessage, deliver},
+};
+
+/// Returns owner-only commands which fail on purpose, one for each kind of
+/// error [`poise_error`][crate] handles, so that you can see what users see:
+///
+/// - `/diagnose user` returns a [`UserError`][crate::UserError].
+/// - `/diagnose notice` returns a [`Notice`].
+/// - `/diagnose internal` returns an internal error.
+/// - `/diagnose panic` panics (only handled if [`poise`]'s `handle_panics`
+///   feature is enabled).
+/// - `/diagnose cooldown` hits its cooldown when invoked twice within a minute.
+/// - `/diagnose check` fails its check.
+/// - `/diagnose permissions` requires the bot to be an administrator, so it
+///   fails in servers where it is not.
+/// - `/diagnose argument` has an argument which can never be parsed.
+/// - `/diagnose` on its own requires a subcommand.
+///
+/// The cooldown, check and permissions diagnostics do not fail if
+/// [`poise::FrameworkOptions::skip_checks_for_owners`] is enabled.
+///
+/// Only available with the `diagnostics` feature.
+///
+/// # Examples
+///
+/// ```
+/// use poise_error::{diagnostic_commands, on_error};
+///
+/// let mut commands = vec![/* your commands */];
+///
+/// commands.extend(diagnostic_commands());
+///
+/// let framework = poise::Framework::builder()
+///     .options(poise::FrameworkOptions {
+///         commands,
+///         on_error,
+///         ..Default::default()
+///     })
+///     .setup(|ctx, _ready, framework| {
+///         Box::pin(async move { Ok(()) })
+///     })
+///     .build();
+/// ```
+pub fn diagnostic_commands<U: Send + Sync + 'static>() -> Vec<poise::Command<U, anyhow::Error>> {
+    vec![diagnose()]
+}
+
+/// An argument which can never be parsed.
+struct Unparsable;
+
+/// The error returned when parsing [`Unparsable`].
+#[derive(Error, Debug)]
+#[error("This argument can never be parsed")]
+struct UnparsableError;
+
+impl FromStr for Unparsable {
+    type Err = UnparsableError;
+
+    fn from_str(_s: &str) -> Result<Self, Self::Err> {
+        Err(UnparsableError)
+    }
+}
+
+/// A check which always fails.
+async fn failing_check<U: Send + Sync + 'static>(
+    _ctx: Context<'_, U>,
+) -> Result<bool, anyhow::Error> {
+    bail!("This check failed on purpose")
+}
+
+/// Fails on purpose to show how errors are handled
+#[command(
+    slash_command,
+    prefix_command,
+    owners_only,
+    default_member_permissions = "ADMINISTRATOR",
+    subcommands(
+        "user",
+        "notice",
+        "internal",
+        "panic",
+        "cooldown",
+        "check",
+        "permissions",
+        "argument"
+    ),
+    subcommand_required
+)]
+async fn diagnose<U: Send + Sync + 'static>(_ctx: Context<'_, U>) -> Result<(), anyhow::Error> {
+    Ok(())
+}
+
+/// Returns a user error
+#[command(slash_command, prefix_command, owners_only)]
+async fn user<U: Send + Sync + 'static>(_ctx: Context<'_, U>) -> Result<(), anyhow::Error> {
+    Err(anyhow!("This is an example of a user error"))
+        .user_context("This is an example of extra context")?
+}
+
+/// Returns a notice
+#[command(slash_command, prefix_command, owners_only)]
+async fn notice<U: Send + Sync + 'static>(_ctx: Context<'_, U>) -> Result<(), anyhow::Error> {
+    bail!(Notice(anyhow!("This is an example of a notice")))
+}
+
+/// Returns an internal error
+#[command(slash_command, prefix_command, owners_only)]
+async fn internal<U: Send + Sync + 'static>(_ctx: Context<'_, U>) -> Result<(), anyhow::Error> {
+    Err(anyhow!("This is an example of an internal error")
+        .context("This is an example of extra context"))
+}
+
+/// Panics
+#[command(slash_command, prefix_command, owners_only)]
+async fn panic<U: Send + Sync + 'static>(_ctx: Context<'_, U>) -> Result<(), anyhow::Error> {
+    panic!("This is an example of a panic")
+}
+
+/// Hits its cooldown when invoked twice within a minute
+#[command(slash_command, prefix_command, owners_only, user_cooldown = 60)]
+async fn cooldown<U: Send + Sync + 'static>(ctx: Context<'_, U>) -> Result<(), anyhow::Error> {
+    deliver(
+        ctx,
+        &ErrorMessage::new()
+            .title("Cooldown started")
+            .description("Invoke this command again within a minute to hit its cooldown.")
+            .color(BLURPLE),
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Fails its check
+#[command(slash_command, prefix_command, owners_only, check = "failing_check")]
+async fn check<U: Send + Sync + 'static>(_ctx: Context<'_, U>) -> Result<(), anyhow::Error> {
+    Ok(())
+}
+
+/// Requires the bot to be an administrator
+#[command(
+    slash_command,
+    prefix_command,
+    owners_only,
+    guild_only,
+    required_bot_permissions = "ADMINISTRATOR"
+)]
+async fn permissions<U: Send + Sync + 'static>(_ctx: Context<'_, U>) -> Result<(), anyhow::Error> {
+    Ok(())
+}
+
+/// Has an argument which can never be parsed
+#[command(slash_command, prefix_command, owners_only)]
+async fn argument<U: Send + Sync + 'static>(
+    _ctx: Context<'_, U>,
+    #[description = "Anything, it will fail to parse"] value: Unparsable,
+) -> Result<(), anyhow::Error> {
+    let Unparsable = value;
+
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use poise::{command, serenity_prelude::colours::branding::BLURPLE};
+
+use crate::{
+    Context, ErrorHandler, UserContext,
+    message::{ErrorMessage, deliver},
+};
+
+/// Maximum number of codes suggested while typing.
+const MAX_SUGGESTIONS: usize = 25;
+
+/// Returns an `/explain <code>` command, which shows the long-form explanation
+/// of an error code from the installed handler's catalog (see
+/// [`ErrorHandler::catalog`]).
+///
+/// Error codes are shown in the footer of messages about errors, so users can
+/// look them up themselves or mention them when asking for help.
+///
+/// # Examples
+///
+/// ```
+/// use poise_error::{explain_commands, on_error};
+///
+/// let mut commands = vec![/* your commands */];
+///
+/// commands.extend(explain_commands());
+///
+/// let framework = poise::Framework::builder()
+///     .options(poise::FrameworkOptions {
+///         commands,
+///         on_error,
+///         ..Default::default()
+///     })
+///     .setup(|ctx, _ready, framework| {
+///         Box::pin(async move { Ok(()) })
+///     })
+///     .build();
+/// ```
+pub fn explain_commands<U: Send + Sync + 'static>() -> Vec<poise::Command<U, anyhow::Error>> {
+    vec![explain()]
+}
+
+/// Suggests codes from the catalog which contain what has been typed so far.
+async fn autocomplete_code<U: Send + Sync + 'static>(
+    _ctx: Context<'_, U>,
+    partial: &str,
+) -> Vec<String> {
+    let partial = partial.trim().to_uppercase();
+
+    ErrorHandler::get()
+        .error_catalog()
+        .codes()
+        .filter(|code| code.contains(&partial))
+        .take(MAX_SUGGESTIONS)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Explains an error code
+#[command(slash_command, prefix_command)]
+async fn explain<U: Send + Sync + 'static>(
+    ctx: Context<'_, U>,
+    #[description = "Error code, such as PE-COOLDOWN"]
+    #[autocomplete = "autocomplete_code"]
+    code: String,
+) -> Result<(), anyhow::Error> {
+    let code = code.trim().to_uppercase();
+    let explanation = ErrorHandler::get()
+        .error_catalog()
+        .get(&code)
+        .with_user_context(|| format!("There is no error code `{code}`"))?;
+
+    deliver(
+        ctx,
+        &ErrorMessage::new()
+            .title(format!("Error code `{code}`"))
+            .description(explanation)
+            .color(BLURPLE),
+    )
+    .await;
+
+    Ok(())
+}
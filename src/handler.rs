@@ -0,0 +1,287 @@
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::bail;
+
+use crate::{
+    catalog::ErrorCatalog,
+    config::MessagesHandle,
+    hooks::{AfterReply, BeforeHandle, Hooks},
+    incident::IncidentStore,
+    message::{DeferredReply, RenderMode},
+    register::{Registrar, Registration},
+    scrub::Scrubber,
+    verbosity::{Audience, Verbosity},
+};
+
+static HANDLER: OnceLock<ErrorHandler> = OnceLock::new();
+
+/// Configuration for how [`poise_error`][crate] handles errors.
+///
+/// Installing [`ErrorHandler::default()`] is the same as installing nothing at
+/// all.
+///
+/// # Examples
+///
+/// ```
+/// use poise::serenity_prelude::RoleId;
+/// use poise_error::{Audience, ErrorHandler, Verbosity};
+///
+/// ErrorHandler::new()
+///     .verbosity(Verbosity::Minimal)
+///     .verbose_audience(Audience::owners().role(RoleId::new(1234)))
+///     .suggest_unknown_commands(true)
+///     .install()
+///     .unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct ErrorHandler {
+    pub(crate) render_mode: RenderMode,
+    pub(crate) deferred_reply: DeferredReply,
+    pub(crate) scrubber: Scrubber,
+    pub(crate) verbosity: Mutex<Verbosity>,
+    pub(crate) verbose_audience: Audience,
+    pub(crate) suggest_unknown_commands: bool,
+    pub(crate) pick_subcommands: bool,
+    pub(crate) reregister_unknown_interactions: Option<Registration>,
+    pub(crate) reregister_mismatched_commands: Option<Registration>,
+    pub(crate) registrar: Registrar,
+    pub(crate) incident_store: Option<Box<dyn IncidentStore>>,
+    pub(crate) hooks: Hooks,
+    pub(crate) messages: MessagesHandle,
+    pub(crate) catalog: ErrorCatalog,
+}
+
+impl ErrorHandler {
+    /// Creates a new [`ErrorHandler`] with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how messages presented to users are rendered.
+    ///
+    /// Defaults to [`RenderMode::Embed`].
+    pub fn render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+
+    /// Sets how slash commands which deferred their response before failing are
+    /// replied to. If it is too late to respond to a slash command, the message
+    /// is sent to its channel instead, unless it should be ephemeral, in which
+    /// case it is sent to the user's DMs.
+    ///
+    /// Defaults to [`DeferredReply::Auto`].
+    pub fn deferred_reply(mut self, deferred_reply: DeferredReply) -> Self {
+        self.deferred_reply = deferred_reply;
+        self
+    }
+
+    /// Sets how secrets are removed from everything shown to users or logged.
+    ///
+    /// Defaults to [`Scrubber::default()`].
+    pub fn scrubber(mut self, scrubber: Scrubber) -> Self {
+        self.scrubber = scrubber;
+        self
+    }
+
+    /// Sets how much detail about errors is shown to users, other than those
+    /// in the verbose audience (see [`ErrorHandler::verbose_audience`]).
+    ///
+    /// Defaults to [`Verbosity::Normal`].
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = Mutex::new(verbosity);
+        self
+    }
+
+    /// Returns how much detail about errors is currently shown to users (see
+    /// [`ErrorHandler::verbosity`]).
+    pub fn current_verbosity(&self) -> Verbosity {
+        *self.verbosity.lock().unwrap()
+    }
+
+    /// Changes how much detail about errors is shown to users while the bot is
+    /// running (see [`ErrorHandler::verbosity`]).
+    pub fn set_verbosity(&self, verbosity: Verbosity) {
+        *self.verbosity.lock().unwrap() = verbosity;
+    }
+
+    /// Sets who is always shown errors with [`Verbosity::Verbose`], such as
+    /// the bot's owners and staff.
+    ///
+    /// Defaults to [`Audience::owners()`].
+    pub fn verbose_audience(mut self, verbose_audience: Audience) -> Self {
+        self.verbose_audience = verbose_audience;
+        self
+    }
+
+    /// Replies to prefix messages which do not match any command with the
+    /// closest matching command names and aliases, if any are close enough.
+    ///
+    /// Messages which are not similar to any command are ignored, so that
+    /// messages meant for other bots sharing the same prefix go unanswered.
+    pub fn suggest_unknown_commands(mut self, suggest_unknown_commands: bool) -> Self {
+        self.suggest_unknown_commands = suggest_unknown_commands;
+        self
+    }
+
+    /// Presents buttons, or a select menu if there are many, for picking a
+    /// subcommand when a command is invoked without one.
+    ///
+    /// Picking a subcommand invokes it, unless it has required parameters, in
+    /// which case its usage is shown instead. Only the first 25 subcommands can
+    /// be picked from a select menu.
+    ///
+    /// Handling the error waits up to a minute for a subcommand to be picked,
+    /// and the picked subcommand runs before handling finishes, so
+    /// [`after_reply`][Self::after_reply] hooks only run afterwards.
+    pub fn pick_subcommands(mut self, pick_subcommands: bool) -> Self {
+        self.pick_subcommands = pick_subcommands;
+        self
+    }
+
+    /// Registers the framework's commands again when an interaction for an
+    /// unknown command is received.
+    ///
+    /// Registrations are debounced, so a burst of unknown interactions only
+    /// causes commands to be registered once.
+    pub fn reregister_unknown_interactions(mut self, registration: Registration) -> Self {
+        self.reregister_unknown_interactions = Some(registration);
+        self
+    }
+
+    /// Registers the framework's commands again when a command's registered
+    /// structure does not match its definition, which usually happens right
+    /// after changing a command without registering it again.
+    ///
+    /// Instead of reporting the mismatch as an internal error, the user is
+    /// asked to try again in a moment. Only one registration runs at a time,
+    /// and registrations are debounced.
+    pub fn reregister_mismatched_commands(mut self, registration: Registration) -> Self {
+        self.reregister_mismatched_commands = Some(registration);
+        self
+    }
+
+    /// Records every internal error which is handled in `incident_store`, and
+    /// shows users the ID of the incident so that they can refer to it when
+    /// asking for help.
+    ///
+    /// Incidents can be looked up with [`ErrorHandler::incidents`].
+    pub fn incident_store(mut self, incident_store: impl IncidentStore + 'static) -> Self {
+        self.incident_store = Some(Box::new(incident_store));
+        self
+    }
+
+    /// Returns the store incidents are recorded in, if any (see
+    /// [`ErrorHandler::incident_store`]).
+    pub fn incidents(&self) -> Option<&dyn IncidentStore> {
+        self.incident_store.as_deref()
+    }
+
+    /// Sets the wording and styling of the messages sent about errors, which
+    /// can be reloaded through `messages` while the bot is running.
+    ///
+    /// Defaults to the built-in messages.
+    pub fn messages(mut self, messages: MessagesHandle) -> Self {
+        self.messages = messages;
+        self
+    }
+
+    /// Sets the catalog of error codes, which gives your own error types codes
+    /// and explains codes through `/explain` (see
+    /// [`explain_commands`][crate::explain_commands]).
+    ///
+    /// Defaults to [`ErrorCatalog::new()`].
+    pub fn catalog(mut self, catalog: ErrorCatalog) -> Self {
+        self.catalog = catalog;
+        self
+    }
+
+    /// Returns the catalog of error codes (see [`ErrorHandler::catalog`]).
+    pub fn error_catalog(&self) -> &ErrorCatalog {
+        &self.catalog
+    }
+
+    /// Runs `hook` before each error is handled, which may suppress the error
+    /// or replace it with another.
+    ///
+    /// `U` must be the same type as your [`poise::Framework`]'s data, otherwise
+    /// the hook never runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poise::FrameworkError;
+    /// use poise_error::ErrorHandler;
+    ///
+    /// struct Data;
+    ///
+    /// let handler = ErrorHandler::new().before_handle::<Data>(|error| {
+    ///     Box::pin(async move {
+    ///         match error {
+    ///             // Not worth telling anyone about
+    ///             FrameworkError::CommandCheckFailed { error: None, .. } => None,
+    ///             other => Some(other),
+    ///         }
+    ///     })
+    /// });
+    /// ```
+    pub fn before_handle<U: 'static>(mut self, hook: BeforeHandle<U>) -> Self {
+        self.hooks.before_handle = Some(Box::new(hook));
+        self
+    }
+
+    /// Runs `hook` after each error has been handled, such as to clean up
+    /// after the command which failed or to edit the reply.
+    ///
+    /// Also run if replying to the error failed, in which case the failure is
+    /// returned from [`try_handle_error`][crate::try_handle_error] afterwards.
+    /// When subcommands are picked (see
+    /// [`pick_subcommands`][Self::pick_subcommands]), only run once one has
+    /// been picked and run, or the picker has timed out.
+    /// Not run for errors which [`poise_error`][crate] does not know how to
+    /// handle and passes on to [`poise::builtins::on_error`].
+    ///
+    /// `U` must be the same type as your [`poise::Framework`]'s data, otherwise
+    /// the hook never runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use poise::FrameworkError;
+    /// use poise_error::ErrorHandler;
+    /// use tracing::info;
+    ///
+    /// struct Data;
+    ///
+    /// let handler = ErrorHandler::new().after_reply::<Data>(|error, reply| {
+    ///     Box::pin(async move {
+    ///         if let FrameworkError::Command { ctx, .. } = error {
+    ///             info!("Replied to failed command {}: {}", ctx.command().name, reply.is_some());
+    ///         }
+    ///     })
+    /// });
+    /// ```
+    pub fn after_reply<U: 'static>(mut self, hook: AfterReply<U>) -> Self {
+        self.hooks.after_reply = Some(Box::new(hook));
+        self
+    }
+
+    /// Makes this the handler used by [`on_error`][crate::on_error] and
+    /// [`try_handle_error`][crate::try_handle_error].
+    ///
+    /// Fails if a handler has already been installed, or if an error has
+    /// already been handled with the default handler.
+    pub fn install(self) -> Result<(), anyhow::Error> {
+        if HANDLER.set(self).is_err() {
+            bail!("An error handler has already been installed");
+        }
+
+        Ok(())
+    }
+
+    /// Returns the installed handler, installing the default one if none has
+    /// been installed yet.
+    pub fn get() -> &'static Self {
+        HANDLER.get_or_init(Self::default)
+    }
+}
@@ -0,0 +1,63 @@
+use std::any::Any;
+
+use poise::{BoxFuture, FrameworkError, ReplyHandle};
+
+use crate::ErrorHandler;
+
+/// A hook which runs before an error is handled (see
+/// [`ErrorHandler::before_handle`]).
+///
+/// Returning [`None`] suppresses the error, returning an error (either the one
+/// given or a replacement) lets [`poise_error`][crate] handle it.
+pub type BeforeHandle<U> =
+    for<'a> fn(
+        FrameworkError<'a, U, anyhow::Error>,
+    ) -> BoxFuture<'a, Option<FrameworkError<'a, U, anyhow::Error>>>;
+
+/// A hook which runs after an error has been handled (see
+/// [`ErrorHandler::after_reply`]).
+///
+/// Given the error and, if the error was replied to with a message which can
+/// be edited or deleted, a handle to the reply.
+pub type AfterReply<U> = for<'a, 'b> fn(
+    &'a FrameworkError<'b, U, anyhow::Error>,
+    Option<&'a ReplyHandle<'b>>,
+) -> BoxFuture<'a, ()>;
+
+/// Hooks of any type, which are only run if their type matches the errors
+/// being handled.
+#[derive(Debug, Default)]
+pub(crate) struct Hooks {
+    pub(crate) before_handle: Option<Box<dyn Any + Send + Sync>>,
+    pub(crate) after_reply: Option<Box<dyn Any + Send + Sync>>,
+}
+
+/// Runs the installed handler's [`BeforeHandle`] hook, if it has one for `U`.
+pub(crate) async fn before_handle<U: 'static>(
+    error: FrameworkError<'_, U, anyhow::Error>,
+) -> Option<FrameworkError<'_, U, anyhow::Error>> {
+    match ErrorHandler::get()
+        .hooks
+        .before_handle
+        .as_ref()
+        .and_then(|hook| hook.downcast_ref::<BeforeHandle<U>>())
+    {
+        Some(hook) => hook(error).await,
+        None => Some(error),
+    }
+}
+
+/// Runs the installed handler's [`AfterReply`] hook, if it has one for `U`.
+pub(crate) async fn after_reply<U: 'static>(
+    error: &FrameworkError<'_, U, anyhow::Error>,
+    reply: Option<&ReplyHandle<'_>>,
+) {
+    if let Some(hook) = ErrorHandler::get()
+        .hooks
+        .after_reply
+        .as_ref()
+        .and_then(|hook| hook.downcast_ref::<AfterReply<U>>())
+    {
+        hook(error, reply).await;
+    }
+}
@@ -0,0 +1,597 @@
+use std::{
+    backtrace::BacktraceStatus,
+    collections::VecDeque,
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, ErrorKind, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use poise::serenity_prelude::{ChannelId, GuildId, Timestamp, UserId};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{Context, ErrorHandler, policy, scrub::scrub};
+
+/// An internal error which was handled, as recorded in an [`IncidentStore`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Incident {
+    /// Short identifier shown to the user who encountered this incident, so
+    /// that they can refer to it when asking for help.
+    pub id: String,
+    /// When this incident occurred.
+    pub timestamp: Timestamp,
+    /// Identifier shared by incidents with the same command and error chain,
+    /// ignoring any numbers in the chain.
+    pub fingerprint: String,
+    /// The error's chain, outermost first, without duplicates or secrets.
+    pub chain: Vec<String>,
+    /// The error's backtrace, if one was captured.
+    pub backtrace: Option<String>,
+    /// The qualified name of the command being invoked, if any.
+    pub command: Option<String>,
+    /// The invocation string of the command being invoked, if any.
+    pub invocation: Option<String>,
+    /// The user who invoked the command, if any.
+    pub user_id: Option<UserId>,
+    /// The guild the command was invoked in, if any.
+    pub guild_id: Option<GuildId>,
+    /// The channel the command was invoked in, if any.
+    pub channel_id: Option<ChannelId>,
+}
+
+/// Storage for [`Incident`]s (see [`ErrorHandler::incident_store`]).
+///
+/// Implemented by [`MemoryStore`], [`JsonLinesStore`] and, with the `sqlite`
+/// feature, `SqliteStore`. Stores are expected to be local and fast, as
+/// incidents are recorded while the error is being handled.
+pub trait IncidentStore: Debug + Send + Sync {
+    /// Saves `incident`.
+    fn record(&self, incident: &Incident) -> Result<(), anyhow::Error>;
+
+    /// Returns the incident with the ID `id`, if there is one.
+    fn get(&self, id: &str) -> Result<Option<Incident>, anyhow::Error>;
+
+    /// Returns up to `limit` of the most recent incidents, most recent first.
+    fn recent(&self, limit: usize) -> Result<Vec<Incident>, anyhow::Error>;
+
+    /// Returns up to `limit` of the most recent incidents which occurred while
+    /// invoking the command with the qualified name `command`, most recent
+    /// first.
+    fn by_command(&self, command: &str, limit: usize) -> Result<Vec<Incident>, anyhow::Error>;
+}
+
+/// An [`IncidentStore`] which keeps the most recent incidents in memory,
+/// forgetting the oldest ones once it is full and all of them when the bot
+/// stops.
+///
+/// # Examples
+///
+/// ```
+/// use poise_error::{ErrorHandler, MemoryStore};
+///
+/// let handler = ErrorHandler::new().incident_store(MemoryStore::new(100));
+/// ```
+#[derive(Debug)]
+pub struct MemoryStore {
+    capacity: usize,
+    incidents: Mutex<VecDeque<Incident>>,
+}
+
+impl MemoryStore {
+    /// Creates a [`MemoryStore`] which keeps up to `capacity` incidents.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            incidents: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Returns the most recent incidents which match `filter`, most recent
+    /// first, stopping after `limit`.
+    fn find(&self, limit: usize, filter: impl Fn(&Incident) -> bool) -> Vec<Incident> {
+        self.incidents
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|incident| filter(incident))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+impl IncidentStore for MemoryStore {
+    fn record(&self, incident: &Incident) -> Result<(), anyhow::Error> {
+        let mut incidents = self.incidents.lock().unwrap();
+
+        if self.capacity == 0 {
+            return Ok(());
+        }
+
+        if incidents.len() == self.capacity {
+            incidents.pop_front();
+        }
+
+        incidents.push_back(incident.clone());
+
+        Ok(())
+    }
+
+    fn get(&self, id: &str) -> Result<Option<Incident>, anyhow::Error> {
+        Ok(self
+            .find(1, |incident| incident.id.eq_ignore_ascii_case(id))
+            .pop())
+    }
+
+    fn recent(&self, limit: usize) -> Result<Vec<Incident>, anyhow::Error> {
+        Ok(self.find(limit, |_| true))
+    }
+
+    fn by_command(&self, command: &str, limit: usize) -> Result<Vec<Incident>, anyhow::Error> {
+        Ok(self.find(limit, |incident| {
+            incident.command.as_deref() == Some(command)
+        }))
+    }
+}
+
+/// An [`IncidentStore`] which appends incidents to a file as JSON, one per
+/// line.
+///
+/// # Examples
+///
+/// ```no_run
+/// use poise_error::{ErrorHandler, IncidentStore, JsonLinesStore};
+///
+/// let handler = ErrorHandler::new().incident_store(JsonLinesStore::new("incidents.jsonl"));
+///
+/// // Later, to answer a support ticket:
+/// if let Some(store) = ErrorHandler::get().incidents() {
+///     let incident = store.get("1A2B3C4D5E6F").unwrap();
+/// }
+/// ```
+///
+/// Lines which are not valid incidents, such as one left half-written by a
+/// crash, are skipped when reading the file.
+#[derive(Debug)]
+pub struct JsonLinesStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl JsonLinesStore {
+    /// Creates a [`JsonLinesStore`] which stores incidents in the file at
+    /// `path`, creating it when the first incident is recorded.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Returns every incident in the file which matches `filter`, most recent
+    /// first, stopping after `limit`. Lines which are not valid incidents, such
+    /// as one left half-written by a crash, are logged and skipped.
+    fn find(
+        &self,
+        limit: usize,
+        filter: impl Fn(&Incident) -> bool,
+    ) -> Result<Vec<Incident>, anyhow::Error> {
+        let _lock = self.lock.lock().unwrap();
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut incidents = Vec::new();
+
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let incident: Incident = match serde_json::from_str(&line) {
+                Ok(incident) => incident,
+                Err(err) => {
+                    warn!(
+                        "Skipping malformed incident on line {} of {:?}: {err}",
+                        index + 1,
+                        self.path,
+                    );
+
+                    continue;
+                }
+            };
+
+            if filter(&incident) {
+                incidents.push(incident);
+            }
+        }
+
+        Ok(incidents.into_iter().rev().take(limit).collect())
+    }
+}
+
+impl IncidentStore for JsonLinesStore {
+    fn record(&self, incident: &Incident) -> Result<(), anyhow::Error> {
+        let _lock = self.lock.lock().unwrap();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut line = serde_json::to_string(incident)?;
+
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn get(&self, id: &str) -> Result<Option<Incident>, anyhow::Error> {
+        Ok(self
+            .find(1, |incident| incident.id.eq_ignore_ascii_case(id))?
+            .pop())
+    }
+
+    fn recent(&self, limit: usize) -> Result<Vec<Incident>, anyhow::Error> {
+        self.find(limit, |_| true)
+    }
+
+    fn by_command(&self, command: &str, limit: usize) -> Result<Vec<Incident>, anyhow::Error> {
+        self.find(limit, |incident| {
+            incident.command.as_deref() == Some(command)
+        })
+    }
+}
+
+/// An [`IncidentStore`] which stores incidents in an SQLite database.
+///
+/// # Examples
+///
+/// ```no_run
+/// use poise_error::{ErrorHandler, SqliteStore};
+///
+/// let handler = ErrorHandler::new().incident_store(SqliteStore::open("incidents.db").unwrap());
+/// ```
+#[cfg(feature = "sqlite")]
+#[derive(Debug)]
+pub struct SqliteStore {
+    connection: Mutex<rusqlite::Connection>,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteStore {
+    /// Opens the SQLite database at `path`, creating it and the `incidents`
+    /// table if they do not exist yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let connection = rusqlite::Connection::open(path)?;
+
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS incidents (
+                id TEXT PRIMARY KEY COLLATE NOCASE,
+                timestamp TEXT NOT NULL,
+                command TEXT,
+                incident TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS incidents_command ON incidents (command, timestamp);",
+        )?;
+
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Runs `query` and returns the incidents it selects.
+    fn query(
+        &self,
+        query: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<Incident>, anyhow::Error> {
+        let connection = self.connection.lock().unwrap();
+        let mut statement = connection.prepare(query)?;
+        let rows = statement.query_map(params, |row| row.get::<_, String>(0))?;
+        let mut incidents = Vec::new();
+
+        for row in rows {
+            incidents.push(serde_json::from_str(&row?)?);
+        }
+
+        Ok(incidents)
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl IncidentStore for SqliteStore {
+    fn record(&self, incident: &Incident) -> Result<(), anyhow::Error> {
+        self.connection.lock().unwrap().execute(
+            "INSERT INTO incidents (id, timestamp, command, incident) VALUES (?1, ?2, ?3, ?4)",
+            (
+                &incident.id,
+                incident.timestamp.to_rfc3339(),
+                &incident.command,
+                serde_json::to_string(incident)?,
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    fn get(&self, id: &str) -> Result<Option<Incident>, anyhow::Error> {
+        Ok(self
+            .query("SELECT incident FROM incidents WHERE id = ?1", [id])?
+            .pop())
+    }
+
+    fn recent(&self, limit: usize) -> Result<Vec<Incident>, anyhow::Error> {
+        self.query(
+            "SELECT incident FROM incidents ORDER BY timestamp DESC, rowid DESC LIMIT ?1",
+            [limit as i64],
+        )
+    }
+
+    fn by_command(&self, command: &str, limit: usize) -> Result<Vec<Incident>, anyhow::Error> {
+        self.query(
+            "SELECT incident FROM incidents WHERE command = ?1 ORDER BY timestamp DESC, rowid DESC LIMIT ?2",
+            (command, limit as i64),
+        )
+    }
+}
+
+/// Hashes `bytes` with 64-bit FNV-1a, which is stable across builds.
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Generates a new, short incident ID.
+fn new_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let hash = fnv1a(
+        nanos
+            .to_le_bytes()
+            .into_iter()
+            .chain(count.to_le_bytes())
+            .chain(process::id().to_le_bytes()),
+    );
+
+    format!("{:012X}", hash >> 16)
+}
+
+/// Computes the fingerprint of an error with `chain` in `command`.
+fn fingerprint(command: Option<&str>, chain: &[String]) -> String {
+    let text = chain.iter().fold(
+        command.unwrap_or_default().to_string(),
+        |mut text, message| {
+            text.push('\n');
+            text.push_str(message);
+            text
+        },
+    );
+    let hash = fnv1a(
+        text.bytes()
+            .map(|byte| if byte.is_ascii_digit() { b'#' } else { byte }),
+    );
+
+    format!("{hash:016x}")
+}
+
+/// Records an incident for an internal error with `chain` and `backtrace`
+/// which occurred while handling `ctx`, if any, in the installed handler's
+/// [`IncidentStore`].
+///
+/// Returns the incident's ID, or nothing if there is no store, the invoked
+/// command's [`ErrorPolicy`][crate::ErrorPolicy] disables recording or the
+/// incident could not be recorded.
+pub(crate) fn record<U>(
+    ctx: Option<Context<'_, U>>,
+    chain: Vec<String>,
+    backtrace: Option<String>,
+) -> Option<String> {
+    let store = ErrorHandler::get().incidents()?;
+
+    if let Some(ctx) = ctx
+        && !policy::of(ctx).record_incidents.unwrap_or(true)
+    {
+        return None;
+    }
+
+    let mut chain: Vec<String> = chain.iter().map(|message| scrub(message)).collect();
+
+    chain.dedup();
+
+    let command = ctx.map(|ctx| ctx.command().qualified_name.clone());
+    let incident = Incident {
+        id: new_id(),
+        timestamp: Timestamp::now(),
+        fingerprint: fingerprint(command.as_deref(), &chain),
+        chain,
+        backtrace: backtrace.map(|backtrace| scrub(&backtrace)),
+        command,
+        invocation: ctx.map(|ctx| scrub(&ctx.invocation_string())),
+        user_id: ctx.map(|ctx| ctx.author().id),
+        guild_id: ctx.and_then(|ctx| ctx.guild_id()),
+        channel_id: ctx.map(|ctx| ctx.channel_id()),
+    };
+
+    match store.record(&incident) {
+        Ok(()) => Some(incident.id),
+        Err(err) => {
+            warn!("Failed to record incident {}: {err:#}", incident.id);
+
+            None
+        }
+    }
+}
+
+/// Records an incident for `error`, see [`record`].
+pub(crate) fn record_error<U>(
+    ctx: Option<Context<'_, U>>,
+    error: &anyhow::Error,
+) -> Option<String> {
+    let backtrace = error.backtrace();
+    let backtrace =
+        (backtrace.status() == BacktraceStatus::Captured).then(|| backtrace.to_string());
+
+    record(
+        ctx,
+        error.chain().map(|err| err.to_string()).collect(),
+        backtrace,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn incident(id: &str, command: &str) -> Incident {
+        Incident {
+            id: id.to_string(),
+            timestamp: Timestamp::from_unix_timestamp(1_700_000_000).unwrap(),
+            fingerprint: fingerprint(Some(command), &["Something went wrong".to_string()]),
+            chain: vec!["Something went wrong".to_string()],
+            backtrace: None,
+            command: Some(command.to_string()),
+            invocation: None,
+            user_id: None,
+            guild_id: None,
+            channel_id: None,
+        }
+    }
+
+    fn ids(incidents: Vec<Incident>) -> Vec<String> {
+        incidents.into_iter().map(|incident| incident.id).collect()
+    }
+
+    /// Records three incidents in `store` and checks that they are read back,
+    /// most recent first.
+    fn round_trip(store: &impl IncidentStore) {
+        let sold = incident("B", "shop sell");
+
+        store.record(&incident("A", "shop buy")).unwrap();
+        store.record(&sold).unwrap();
+        store.record(&incident("C", "shop buy")).unwrap();
+
+        assert_eq!(store.get("b").unwrap(), Some(sold));
+        assert_eq!(store.get("D").unwrap(), None);
+        assert_eq!(ids(store.recent(10).unwrap()), ["C", "B", "A"]);
+        assert_eq!(ids(store.recent(2).unwrap()), ["C", "B"]);
+        assert_eq!(ids(store.by_command("shop buy", 10).unwrap()), ["C", "A"]);
+        assert_eq!(ids(store.by_command("shop buy", 1).unwrap()), ["C"]);
+        assert!(store.by_command("shop", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn memory_store_round_trip() {
+        round_trip(&MemoryStore::new(10));
+    }
+
+    #[test]
+    fn memory_store_forgets_oldest_incidents() {
+        let store = MemoryStore::new(2);
+
+        store.record(&incident("A", "shop buy")).unwrap();
+        store.record(&incident("B", "shop sell")).unwrap();
+        store.record(&incident("C", "shop buy")).unwrap();
+
+        assert_eq!(store.get("A").unwrap(), None);
+        assert_eq!(ids(store.recent(10).unwrap()), ["C", "B"]);
+        assert_eq!(ids(store.by_command("shop buy", 10).unwrap()), ["C"]);
+
+        let store = MemoryStore::new(0);
+
+        store.record(&incident("A", "shop buy")).unwrap();
+
+        assert!(store.recent(10).unwrap().is_empty());
+    }
+
+    /// A [`JsonLinesStore`] in a file which is removed when dropped.
+    struct TempStore(JsonLinesStore);
+
+    impl TempStore {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "poise-error-{name}-{}-{}.jsonl",
+                process::id(),
+                new_id(),
+            ));
+
+            Self(JsonLinesStore::new(path))
+        }
+    }
+
+    impl Drop for TempStore {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0.path);
+        }
+    }
+
+    #[test]
+    fn json_lines_store_round_trip() {
+        let store = TempStore::new("round-trip");
+
+        assert!(store.0.recent(10).unwrap().is_empty());
+
+        round_trip(&store.0);
+    }
+
+    #[test]
+    fn json_lines_store_skips_malformed_lines() {
+        let store = TempStore::new("malformed");
+
+        store.0.record(&incident("A", "shop buy")).unwrap();
+        OpenOptions::new()
+            .append(true)
+            .open(&store.0.path)
+            .unwrap()
+            .write_all(b"not json\n\n")
+            .unwrap();
+        store.0.record(&incident("B", "shop buy")).unwrap();
+        OpenOptions::new()
+            .append(true)
+            .open(&store.0.path)
+            .unwrap()
+            .write_all(br#"{"id":"C","timest"#)
+            .unwrap();
+
+        assert_eq!(ids(store.0.recent(10).unwrap()), ["B", "A"]);
+        assert_eq!(store.0.get("C").unwrap(), None);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_store_round_trip() {
+        round_trip(&SqliteStore::open(":memory:").unwrap());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_store_orders_by_timestamp() {
+        let store = SqliteStore::open(":memory:").unwrap();
+        let later = Incident {
+            timestamp: Timestamp::from_unix_timestamp(1_800_000_000).unwrap(),
+            ..incident("A", "shop buy")
+        };
+
+        store.record(&later).unwrap();
+        store.record(&incident("B", "shop buy")).unwrap();
+
+        assert_eq!(ids(store.recent(10).unwrap()), ["A", "B"]);
+        assert!(store.record(&incident("a", "shop sell")).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+use poise::serenity_prelude::{ComponentInteraction, ModalInteraction};
+
+use crate::{
+    Context, classify::classify_command_error, command_error_message,
+    message::deliver_to_interaction,
+};
+
+/// Handles an error which occurred while handling a component interaction,
+/// such as a button press or select menu choice, during the command invoked
+/// in `ctx`.
+///
+/// Errors are classified, recorded, logged and presented in the same way as
+/// errors returned by the command itself (see
+/// [`try_handle_error`][crate::try_handle_error]), in response to
+/// `interaction`. If `interaction` has already been responded to, the message
+/// is sent as a follow-up instead. [`ErrorHandler::before_handle`] and
+/// [`ErrorHandler::after_reply`] hooks are not run, since these errors do not
+/// come from [`poise`].
+///
+/// [`ErrorHandler::before_handle`]: crate::ErrorHandler::before_handle
+/// [`ErrorHandler::after_reply`]: crate::ErrorHandler::after_reply
+///
+/// # Examples
+///
+/// ```
+/// use poise::serenity_prelude::{
+///     ComponentInteraction, ComponentInteractionCollector, CreateActionRow, CreateButton,
+/// };
+/// use poise_error::{anyhow, handle_interaction_error, user_bail};
+///
+/// async fn on_press(interaction: &ComponentInteraction) -> anyhow::Result<()> {
+///     user_bail!("The shop is closed")
+/// }
+///
+/// #[poise::command(slash_command)]
+/// async fn shop(ctx: poise_error::Context<'_>) -> anyhow::Result<()> {
+///     ctx.send(
+///         poise::CreateReply::default()
+///             .content("Welcome to the shop!")
+///             .components(vec![CreateActionRow::Buttons(vec![
+///                 CreateButton::new("buy").label("Buy"),
+///             ])]),
+///     )
+///     .await?;
+///
+///     while let Some(interaction) = ComponentInteractionCollector::new(ctx)
+///         .author_id(ctx.author().id)
+///         .await
+///     {
+///         if let Err(err) = on_press(&interaction).await {
+///             handle_interaction_error(ctx, &interaction, err).await;
+///         }
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub async fn handle_interaction_error<U>(
+    ctx: Context<'_, U>,
+    interaction: &ComponentInteraction,
+    error: anyhow::Error,
+) {
+    let message = command_error_message(ctx, &error, classify_command_error(&error));
+
+    deliver_to_interaction(ctx, interaction.id, &interaction.token, &message).await;
+}
+
+/// Handles an error which occurred while handling a modal submission during
+/// the command invoked in `ctx`, in the same way as
+/// [`handle_interaction_error`].
+pub async fn handle_modal_error<U>(
+    ctx: Context<'_, U>,
+    interaction: &ModalInteraction,
+    error: anyhow::Error,
+) {
+    let message = command_error_message(ctx, &error, classify_command_error(&error));
+
+    deliver_to_interaction(ctx, interaction.id, &interaction.token, &message).await;
+}
@@ -45,19 +45,61 @@
 //!
 //! [Goober Bot]: https://github.com/valentinegb/goober-bot
 
+mod admin;
+mod catalog;
+mod classify;
+mod config;
+#[cfg(feature = "diagnostics")]
+mod diagnostics;
+mod explain;
+mod handler;
+mod hooks;
+mod incident;
+mod interaction;
+mod message;
+mod policy;
+mod register;
+mod scrub;
+mod subcommand;
+mod suggest;
+mod template;
+mod usage;
+mod user;
+mod verbosity;
+
 use std::{convert::Infallible, str::FromStr};
 
-use poise::{
-    BoxFuture, CreateReply, FrameworkError,
-    serenity_prelude::{
-        CreateEmbed, CreateEmbedFooter, Mentionable,
-        colours::css::{DANGER, WARNING},
-    },
-};
+use poise::{BoxFuture, FrameworkError, serenity_prelude::Mentionable};
 use thiserror::Error;
-use tracing::{error, warn};
+use tracing::{error, info, warn};
 
+use crate::{
+    message::{ErrorMessage, deliver},
+    scrub::scrub,
+    template::{Placeholder, Values},
+};
+
+pub use admin::admin_commands;
 pub use anyhow;
+pub use catalog::ErrorCatalog;
+pub use classify::{Category, ErrorClass, Severity, classify};
+pub use config::{HexColour, MessageConfig, Messages, MessagesHandle, Visibility};
+#[cfg(feature = "diagnostics")]
+pub use diagnostics::diagnostic_commands;
+pub use explain::explain_commands;
+pub use handler::ErrorHandler;
+pub use hooks::{AfterReply, BeforeHandle};
+#[cfg(feature = "sqlite")]
+pub use incident::SqliteStore;
+pub use incident::{Incident, IncidentStore, JsonLinesStore, MemoryStore};
+pub use interaction::{handle_interaction_error, handle_modal_error};
+pub use message::{DeferredReply, RenderMode};
+pub use policy::ErrorPolicy;
+pub use regex;
+pub use register::Registration;
+pub use scrub::Scrubber;
+pub use user::UserContext;
+pub use verbosity::{Audience, Verbosity};
 
 /// A shorthand for the [`poise::Context`] enum.
 ///
@@ -72,6 +114,21 @@ pub type Context<'a, U = ()> = poise::Context<'a, U, anyhow::Error>;
 /// present the user with an embed stating that *they* have made an error as
 /// opposed to the bot having made an error.
 ///
+/// User errors are most easily created with [`user_error!`], [`user_bail!`],
+/// [`user_ensure!`] and [`UserContext`].
+///
+/// A user error does not have to be returned as is: it is found anywhere in
+/// the returned error's chain, such as beneath or attached with
+/// [`anyhow::Context::context`] or inside your own error type, as long as that
+/// type returns it from [`Error::source`][std::error::Error::source]. When a
+/// chain contains both a user error and other errors, the user error takes
+/// precedence and the whole chain is treated as the user's fault: errors above
+/// it are shown as context and errors beneath it as its causes. To report such
+/// an error as the bot's fault instead, convert the user error into a plain
+/// error, for example with `anyhow!(user_error.to_string())`. If a chain
+/// contains both a user error and a [`Notice`], the outermost of the two
+/// decides how it is presented.
+///
 /// # Examples
 ///
 /// ```
@@ -105,6 +162,47 @@ impl FromStr for UserError {
     }
 }
 
+/// An anticipated early exit which is not an error, such as a command having
+/// nothing to do.
+///
+/// Returning this error from a command will present the user with a neutral
+/// embed containing it and the `PE-NOTICE` code, as opposed to stating that
+/// they have made an error. Notices are not logged. Like a [`UserError`], a
+/// notice is found anywhere in the returned error's chain.
+///
+/// # Examples
+///
+/// ```
+/// use std::str::FromStr;
+///
+/// use poise_error::{
+///     anyhow::{self, bail},
+///     Notice,
+/// };
+///
+/// #[poise::command(prefix_command, slash_command)]
+/// async fn inventory(ctx: poise_error::Context<'_>) -> anyhow::Result<()> {
+///     bail!(Notice::from_str("You have no items yet").unwrap())
+/// }
+/// ```
+#[derive(Error, Debug)]
+#[error(transparent)]
+pub struct Notice(#[from] pub anyhow::Error);
+
+impl From<String> for Notice {
+    fn from(value: String) -> Self {
+        Notice(anyhow::anyhow!(value))
+    }
+}
+
+impl FromStr for Notice {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(s.to_string().into())
+    }
+}
+
 /// Removes duplicates from an error's chain.
 ///
 /// This function does not retain any error types; all errors in a chain will
@@ -115,19 +213,120 @@ pub fn dedup_error_chain(error: &mut anyhow::Error) {
     let mut chain: Vec<String> = error.chain().map(|err| err.to_string()).collect();
 
     chain.dedup();
+    *error = error_from_chain(chain);
+}
+
+/// Returns a copy of an error's chain without duplicates or secrets (see
+/// [`Scrubber`]).
+fn clean_error_chain(error: &anyhow::Error) -> anyhow::Error {
+    let mut chain: Vec<String> = error.chain().map(|err| scrub(&err.to_string())).collect();
+
+    chain.dedup();
+    error_from_chain(chain)
+}
 
+/// Creates an error from a chain of messages, outermost first.
+fn error_from_chain(chain: Vec<String>) -> anyhow::Error {
     let mut chain = chain.into_iter().rev();
-    let mut deduped_error = anyhow::anyhow!(chain.next().unwrap());
+    let mut error = anyhow::anyhow!(chain.next().unwrap());
 
     for message in chain {
-        deduped_error = deduped_error.context(message);
+        error = error.context(message);
     }
 
-    *error = deduped_error;
+    error
+}
+
+/// Footer of messages about errors which may be the bot's fault.
+const MAYBE_BOT_ERROR: &str =
+    "If you believe this is an error on the bot's end, please contact a developer.";
+
+/// Footer of messages about errors which are the bot's fault.
+const BOT_ERROR: &str =
+    "This isn't supposed to happen! If you have the time, please contact a developer.";
+
+/// Description of messages about errors whose details are hidden.
+const HIDDEN_DETAILS: &str = "The details of this error have been hidden.";
+
+/// Builds the message presented to the user about `error`, which occurred while
+/// running the command invoked in `ctx`. Internal errors are recorded and
+/// logged.
+fn command_error_message<U>(
+    ctx: Context<'_, U>,
+    error: &anyhow::Error,
+    class: ErrorClass,
+) -> ErrorMessage {
+    let messages = ErrorHandler::get().messages.get();
+    let verbosity = verbosity::of(ctx);
+    let details = verbosity::describe(error, verbosity);
+    let policy = policy::of(ctx);
+
+    if class.severity == Severity::Info {
+        return ErrorMessage::new()
+            .title("Notice")
+            .description(format!("{:#}", clean_error_chain(error)))
+            .color(class.colour())
+            .configure(&messages.notice, &Values::of(ctx))
+            .code(class.code);
+    }
+
+    if class.category == Category::User {
+        let mut message = ErrorMessage::new()
+            .title("You seem to have made an error")
+            .details(details)
+            .footer(MAYBE_BOT_ERROR)
+            .color(class.colour())
+            .configure(&messages.user_error, &Values::of(ctx))
+            .code(class.code);
+
+        if let Some(title) = policy.user_error_title {
+            message = message.title(title);
+        }
+
+        return message;
+    }
+
+    let incident_id = incident::record_error(Some(ctx), error);
+
+    error!(
+        "An error occurred whilst executing {:?}: {:#}",
+        scrub(&ctx.invocation_string()),
+        clean_error_chain(error),
+    );
+
+    let message = match verbosity {
+        Verbosity::Minimal => ErrorMessage::new().description(HIDDEN_DETAILS),
+        _ => ErrorMessage::new().details(details),
+    };
+    let mut message = message
+        .title("An internal error has occurred")
+        .footer(BOT_ERROR)
+        .color(class.colour())
+        .configure(
+            &messages.internal_error,
+            &Values::of(ctx).with(
+                Placeholder::IncidentId,
+                incident_id.clone().unwrap_or_default(),
+            ),
+        )
+        .code(class.code)
+        .incident_id(incident_id);
+
+    if let Some(title) = policy.internal_error_title {
+        message = message.title(title);
+    }
+
+    if let Some(description) = policy.internal_error_description {
+        message = message.description(description);
+    }
+
+    message
 }
 
 /// Handles errors given by [`poise`].
 ///
+/// Behaves according to the installed [`ErrorHandler`], if any.
+///
 /// Used internally by [`on_error`]. You can use this instead of [`on_error`] if
 /// you would like to extend the functionality of [`poise_error`][crate].
 ///
@@ -143,7 +342,7 @@ pub fn dedup_error_chain(error: &mut anyhow::Error) {
 /// # #[error("this is my special error :)")]
 /// # struct SpecialError;
 /// #
-/// async fn my_custom_error_handler<U>(
+/// async fn my_custom_error_handler<U: 'static>(
 ///     error: FrameworkError<'_, U, anyhow::Error>,
 /// ) -> Result<(), anyhow::Error> {
 ///     match error {
@@ -175,119 +374,122 @@ pub fn dedup_error_chain(error: &mut anyhow::Error) {
 ///     })
 ///     .build();
 /// ```
-pub async fn try_handle_error<U>(
+pub async fn try_handle_error<U: 'static>(
     error: FrameworkError<'_, U, anyhow::Error>,
 ) -> Result<(), anyhow::Error> {
-    const MAYBE_BOT_ERROR: &str =
-        "If you believe this is an error on the bot's end, please contact a developer.";
-    const BOT_ERROR: &str =
-        "This isn't supposed to happen! If you have the time, please contact a developer.";
-
-    match error {
-        FrameworkError::Setup { mut error, .. } => {
-            dedup_error_chain(&mut error);
-            error!("Failed to complete setup: {error:#}");
+    let handler = ErrorHandler::get();
+    let messages = handler.messages.get();
+    let Some(error) = hooks::before_handle(error).await else {
+        return Ok(());
+    };
+    let class = classify(&error);
+    // Errors from sending replies or registering commands, returned once the
+    // after reply hook has run.
+    let mut failure = None;
+    let reply = match &error {
+        FrameworkError::Setup { error, .. } => {
+            incident::record_error::<U>(None, error);
+            error!("Failed to complete setup: {:#}", clean_error_chain(error));
+
+            None
         }
-        FrameworkError::EventHandler {
-            mut error, event, ..
-        } => {
-            dedup_error_chain(&mut error);
+        FrameworkError::EventHandler { error, event, .. } => {
+            incident::record_error::<U>(None, error);
             error!(
-                "Failed to handle event {:?}: {error:#}",
+                "Failed to handle event {:?}: {:#}",
                 event.snake_case_name(),
+                clean_error_chain(error),
             );
+
+            None
         }
-        FrameworkError::Command { mut error, ctx, .. } => {
-            let invocation_string = ctx.invocation_string();
-            let description = format!("```\n{error:?}\n```");
-
-            if error.is::<UserError>() {
-                dedup_error_chain(&mut error);
-                ctx.send(
-                    CreateReply::default()
-                        .embed(
-                            CreateEmbed::new()
-                                .title("You seem to have made an error")
-                                .description(description)
-                                .footer(CreateEmbedFooter::new(MAYBE_BOT_ERROR))
-                                .color(WARNING),
-                        )
-                        .reply(true)
-                        .ephemeral(true),
-                )
-                .await?;
-            } else {
-                dedup_error_chain(&mut error);
-                error!("An error occurred whilst executing {invocation_string:?}: {error:#}");
-                ctx.send(
-                    CreateReply::default()
-                        .embed(
-                            CreateEmbed::new()
-                                .title("An internal error has occurred")
-                                .description(description)
-                                .footer(CreateEmbedFooter::new(BOT_ERROR))
-                                .color(DANGER),
-                        )
-                        .reply(true)
-                        .ephemeral(true),
-                )
-                .await?;
-            }
+        FrameworkError::Command { error, ctx, .. } => {
+            let ctx = *ctx;
+
+            deliver(ctx, &command_error_message(ctx, error, class)).await
         }
         FrameworkError::SubcommandRequired { ctx } => {
+            let ctx = *ctx;
+
             warn!(
                 "User attempted to invoke a command, which requires a subcommand, without a subcommand: {:?}",
-                ctx.invocation_string(),
+                scrub(&ctx.invocation_string()),
             );
 
-            let prefix = ctx.prefix();
+            let subcommands = subcommand::invocable_subcommands(ctx)
+                .into_iter()
+                .map(|subcommand| {
+                    let invocation = usage::invocation(ctx, subcommand);
 
-            ctx.send(
-                CreateReply::default()
-                    .embed(
-                        CreateEmbed::new()
-                            .title("Subcommand required")
-                            .description(format!(
-                                "You must specify one of the following subcommands:\n\n{}",
-                                ctx.command()
-                                    .subcommands
-                                    .iter()
-                                    .map(|subcommand| {
-                                        if prefix == ctx.framework().bot_id.mention().to_string() {
-                                            format!("- {prefix} `{}`", subcommand.qualified_name)
-                                        } else {
-                                            format!("- `{prefix}{}`", subcommand.qualified_name)
-                                        }
-                                    })
-                                    .collect::<Vec<_>>()
-                                    .join("\n"),
-                            ))
-                            .color(WARNING),
-                    )
-                    .reply(true)
-                    .ephemeral(true),
-            )
-            .await?;
+                    match &subcommand.description {
+                        Some(description) => format!("- {invocation}: {description}"),
+                        None => format!("- {invocation}"),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let message = ErrorMessage::new()
+                .title("Subcommand required")
+                .description(format!(
+                    "You must specify one of the following subcommands:\n\n{subcommands}",
+                ))
+                .color(class.colour())
+                .configure(
+                    &messages.subcommand_required,
+                    &Values::of(ctx).with(Placeholder::Subcommands, subcommands),
+                )
+                .code(class.code);
+
+            if handler.pick_subcommands {
+                subcommand::pick(ctx, &message, class)
+                    .await
+                    .unwrap_or_else(|err| {
+                        failure = Some(err);
+
+                        None
+                    })
+            } else {
+                deliver(ctx, &message).await
+            }
         }
-        FrameworkError::CommandPanic { ctx, .. } => {
-            ctx.send(
-                CreateReply::default()
-                    .embed(
-                        CreateEmbed::new()
-                            .title("Panicked")
-                            .description("A really bad error happened and the bot panicked! You should contact a bot developer and tell them to check the logs.")
-                            .color(DANGER),
-                    )
-                    .reply(true)
-                    .ephemeral(true),
-            )
-            .await?;
+        FrameworkError::CommandPanic { payload, ctx, .. } => {
+            let ctx = *ctx;
+            let incident_id = incident::record(
+                Some(ctx),
+                vec![
+                    payload
+                        .clone()
+                        .unwrap_or_else(|| "Panicked without a payload".to_string()),
+                ],
+                None,
+            );
+            let mut message = ErrorMessage::new()
+                .title("Panicked")
+                .description("A really bad error happened and the bot panicked! You should contact a bot developer and tell them to check the logs.")
+                .color(class.colour())
+                .configure(
+                    &messages.panic,
+                    &Values::of(ctx).with(
+                        Placeholder::IncidentId,
+                        incident_id.clone().unwrap_or_default(),
+                    ),
+                ).code(class.code)
+                .incident_id(incident_id);
+
+            if verbosity::of(ctx) == Verbosity::Verbose
+                && let Some(payload) = payload
+            {
+                message = message.details(payload);
+            }
+
+            deliver(ctx, &message).await
         }
         FrameworkError::ArgumentParse {
             error, input, ctx, ..
         } => {
-            let invocation_string = ctx.invocation_string();
-            let description = match input {
+            let ctx = *ctx;
+            let invocation_string = scrub(&ctx.invocation_string());
+            let description = match &input {
                 Some(input) => {
                     format!(
                         "Failed to parse {input:?} from {invocation_string:?} into an argument: {error}",
@@ -298,60 +500,131 @@ pub async fn try_handle_error<U>(
                 }
             };
 
-            warn!("{description}");
-            ctx.send(
-                CreateReply::default()
-                    .embed(
-                        CreateEmbed::new()
-                            .title("Failed to parse argument")
-                            .description(description)
-                            .footer(CreateEmbedFooter::new(MAYBE_BOT_ERROR))
-                            .color(WARNING),
-                    )
-                    .reply(true)
-                    .ephemeral(true),
+            warn!("{}", scrub(&description));
+
+            let suggestion = input.as_deref().and_then(|input| {
+                suggest::closest(
+                    input,
+                    ctx.command()
+                        .parameters
+                        .iter()
+                        .flat_map(|parameter| &parameter.choices)
+                        .map(|choice| choice.name.as_str()),
+                )
+            });
+            let description = match suggestion {
+                Some(suggestion) => format!("{description}\n\nDid you mean `{suggestion}`?"),
+                None => description,
+            };
+
+            deliver(
+                ctx,
+                &usage::usage_fields(
+                    ctx,
+                    ctx.command(),
+                    ErrorMessage::new()
+                        .title("Failed to parse argument")
+                        .description(description)
+                        .footer(MAYBE_BOT_ERROR)
+                        .color(class.colour())
+                        .configure(&messages.argument_parse, &Values::of(ctx))
+                        .code(class.code),
+                ),
             )
-            .await?;
+            .await
         }
         FrameworkError::CommandStructureMismatch {
             description, ctx, ..
         } => {
             error!(
-                "Mismatch between registered command and poise command for `/{}`: {description}",
+                "Mismatch between registered command and poise command for `/{}`: {}",
                 ctx.command.qualified_name,
+                scrub(description),
             );
-            ctx.send(
-                CreateReply::default()
-                    .embed(
-                        CreateEmbed::new()
+
+            match handler.reregister_mismatched_commands {
+                Some(registration) => {
+                    let reply = deliver(
+                        (*ctx).into(),
+                        &ErrorMessage::new()
+                            .title("Command is being updated")
+                            .description("This command has changed since it was last registered. Please try again in a moment.")
+                            .color(class.colour())
+                            .configure(
+                                &messages.command_being_updated,
+                                &Values::of((*ctx).into()),
+                            ).code(class.code),
+                    )
+                    .await;
+
+                    match handler
+                        .registrar
+                        .register(
+                            registration,
+                            &ctx.serenity_context.http,
+                            &ctx.framework.options().commands,
+                        )
+                        .await
+                    {
+                        Ok(true) => info!(
+                            "Registered commands again, fixing mismatch for `/{}`",
+                            ctx.command.qualified_name,
+                        ),
+                        Ok(false) => {}
+                        Err(err) => error!(
+                            "Failed to register commands again, mismatch for `/{}` remains: {err}",
+                            ctx.command.qualified_name,
+                        ),
+                    }
+
+                    reply
+                }
+                None => {
+                    deliver(
+                        (*ctx).into(),
+                        &ErrorMessage::new()
                             .title("Command structure mismatch")
-                            .description(format!("```\n{description}\n```"))
-                            .footer(CreateEmbedFooter::new(BOT_ERROR))
-                            .color(DANGER),
+                            .details(*description)
+                            .footer(BOT_ERROR)
+                            .color(class.colour())
+                            .configure(
+                                &messages.command_structure_mismatch,
+                                &Values::of((*ctx).into()),
+                            )
+                            .code(class.code),
                     )
-                    .reply(true)
-                    .ephemeral(true),
-            )
-            .await?;
+                    .await
+                }
+            }
         }
         FrameworkError::CooldownHit {
             remaining_cooldown,
             ctx,
             ..
         } => {
-            warn!("User hit cooldown with {:?}", ctx.invocation_string());
-            ctx.send(
-                CreateReply::default()
-                    .embed(
-                        CreateEmbed::new()
-                            .title("Cooldown hit")
-                            .description(format!("You must wait **~{} seconds** before you can use this command again.", remaining_cooldown.as_secs()))
-                            .color(WARNING),
+            warn!(
+                "User hit cooldown with {:?}",
+                scrub(&ctx.invocation_string())
+            );
+            deliver(
+                *ctx,
+                &ErrorMessage::new()
+                    .title("Cooldown hit")
+                    .description(format!(
+                        "You must wait **~{} seconds** before you can use this command again.",
+                        remaining_cooldown.as_secs()
+                    ))
+                    .color(class.colour())
+                    .configure(
+                        &messages.cooldown_hit,
+                        &Values::of(*ctx).with(
+                            Placeholder::Remaining,
+                            remaining_cooldown.as_secs().to_string(),
+                        ),
                     )
-                    .reply(true)
-                    .ephemeral(true),
+                    .code(class.code),
             )
-            .await?;
+            .await
         }
         FrameworkError::MissingBotPermissions {
             missing_permissions,
@@ -360,20 +633,21 @@ pub async fn try_handle_error<U>(
         } => {
             warn!(
                 "Bot is lacking permissions for {:?}: {missing_permissions}",
-                ctx.invocation_string()
+                scrub(&ctx.invocation_string())
             );
-            ctx.send(
-                CreateReply::default()
-                    .embed(
-                        CreateEmbed::new()
-                            .title("Lacking bot permissions")
-                            .description(format!("The bot requires the following permissions to execute this command: **{missing_permissions}**"))
-                            .color(WARNING),
-                    )
-                    .reply(true)
-                    .ephemeral(true),
+            deliver(
+                *ctx,
+                &ErrorMessage::new()
+                    .title("Lacking bot permissions")
+                    .description(format!("The bot requires the following permissions to execute this command: **{missing_permissions}**"))
+                    .color(class.colour())
+                    .configure(
+                        &messages.missing_bot_permissions,
+                        &Values::of(*ctx)
+                            .with(Placeholder::Permissions, missing_permissions.to_string()),
+                    ).code(class.code),
             )
-            .await?;
+            .await
         }
         FrameworkError::MissingUserPermissions {
             missing_permissions,
@@ -383,160 +657,279 @@ pub async fn try_handle_error<U>(
             Some(missing_permissions) => {
                 warn!(
                     "User is lacking permissions for {:?}: {missing_permissions}",
-                    ctx.invocation_string(),
+                    scrub(&ctx.invocation_string()),
                 );
-                ctx.send(
-                    CreateReply::default()
-                        .embed(
-                            CreateEmbed::new()
-                                .title("Lacking user permissions")
-                                .description(format!("You must have the following permissions to execute this command: **{missing_permissions}**"))
-                                .color(WARNING),
-                        )
-                        .reply(true)
-                        .ephemeral(true),
+                deliver(
+                    *ctx,
+                    &ErrorMessage::new()
+                        .title("Lacking user permissions")
+                        .description(format!("You must have the following permissions to execute this command: **{missing_permissions}**"))
+                        .color(class.colour())
+                        .configure(
+                            &messages.missing_user_permissions,
+                            &Values::of(*ctx)
+                                .with(Placeholder::Permissions, missing_permissions.to_string()),
+                        ).code(class.code),
                 )
-                .await?;
+                .await
             }
             None => {
                 warn!(
                     "User is lacking permissions for {:?}",
-                    ctx.invocation_string(),
+                    scrub(&ctx.invocation_string()),
                 );
-                ctx.send(
-                    CreateReply::default()
-                        .embed(
-                            CreateEmbed::new()
-                                .title("Lacking user permissions")
-                                .description("You do not have the permissions needed to execute this command")
-                                .color(WARNING),
+                deliver(
+                    *ctx,
+                    &ErrorMessage::new()
+                        .title("Lacking user permissions")
+                        .description(
+                            "You do not have the permissions needed to execute this command",
                         )
-                        .reply(true)
-                        .ephemeral(true),
+                        .color(class.colour())
+                        .configure(&messages.missing_user_permissions, &Values::of(*ctx))
+                        .code(class.code),
                 )
-                .await?;
+                .await
             }
         },
         FrameworkError::NotAnOwner { ctx, .. } => {
             warn!(
                 "Non owner attempted to invoke {:?}",
-                ctx.invocation_string(),
+                scrub(&ctx.invocation_string()),
             );
-            ctx.send(
-                CreateReply::default()
-                    .embed(
-                        CreateEmbed::new()
-                            .title("Owner only command")
-                            .description("You must be an owner to use this command.")
-                            .color(WARNING),
-                    )
-                    .reply(true)
-                    .ephemeral(true),
+            deliver(
+                *ctx,
+                &ErrorMessage::new()
+                    .title("Owner only command")
+                    .description("You must be an owner to use this command.")
+                    .color(class.colour())
+                    .configure(&messages.not_an_owner, &Values::of(*ctx))
+                    .code(class.code),
             )
-            .await?;
+            .await
         }
         FrameworkError::GuildOnly { ctx, .. } => {
             warn!(
                 "User attempted to invoke {:?} outside of a guild",
-                ctx.invocation_string(),
+                scrub(&ctx.invocation_string()),
             );
-            ctx.send(
-                CreateReply::default()
-                    .embed(
-                        CreateEmbed::new()
-                            .title("Server only command")
-                            .description("You cannot use this command outside of a server.")
-                            .color(WARNING),
-                    )
-                    .reply(true)
-                    .ephemeral(true),
+            deliver(
+                *ctx,
+                &ErrorMessage::new()
+                    .title("Server only command")
+                    .description("You cannot use this command outside of a server.")
+                    .color(class.colour())
+                    .configure(&messages.guild_only, &Values::of(*ctx))
+                    .code(class.code),
             )
-            .await?;
+            .await
         }
         FrameworkError::DmOnly { ctx, .. } => {
             warn!(
                 "User attempted to invoke {:?} outside of DMs",
-                ctx.invocation_string(),
+                scrub(&ctx.invocation_string()),
             );
-            ctx.send(
-                CreateReply::default()
-                    .embed(
-                        CreateEmbed::new()
-                            .title("DMs only command")
-                            .description("You cannot use this command outside of DMs.")
-                            .color(WARNING),
-                    )
-                    .reply(true)
-                    .ephemeral(true),
+            deliver(
+                *ctx,
+                &ErrorMessage::new()
+                    .title("DMs only command")
+                    .description("You cannot use this command outside of DMs.")
+                    .color(class.colour())
+                    .configure(&messages.dm_only, &Values::of(*ctx))
+                    .code(class.code),
             )
-            .await?;
+            .await
         }
         FrameworkError::NsfwOnly { ctx, .. } => {
             warn!(
                 "User attempted to invoke {:?} outside of an NSFW channel",
-                ctx.invocation_string(),
+                scrub(&ctx.invocation_string()),
             );
-            ctx.send(
-                CreateReply::default()
-                    .embed(
-                        CreateEmbed::new()
-                            .title("NSFW command")
-                            .description("You cannot use this command outside of an NSFW channel.")
-                            .color(WARNING),
-                    )
-                    .reply(true)
-                    .ephemeral(true),
+            deliver(
+                *ctx,
+                &ErrorMessage::new()
+                    .title("NSFW command")
+                    .description("You cannot use this command outside of an NSFW channel.")
+                    .color(class.colour())
+                    .configure(&messages.nsfw_only, &Values::of(*ctx))
+                    .code(class.code),
             )
-            .await?;
+            .await
         }
         FrameworkError::CommandCheckFailed { error, ctx, .. } => match error {
-            Some(mut error) => {
-                dedup_error_chain(&mut error);
-                error!("Check errored for {:?}: {error:#}", ctx.invocation_string());
-                ctx.send(
-                    CreateReply::default()
-                        .embed(
-                            CreateEmbed::new()
-                                .title("Failed to perform check")
-                                .description(format!("```\n{error:?}\n```"))
-                                .footer(CreateEmbedFooter::new(BOT_ERROR))
-                                .color(DANGER),
-                        )
-                        .reply(true)
-                        .ephemeral(true),
-                )
-                .await?;
+            Some(error) => {
+                let ctx = *ctx;
+                let incident_id = incident::record_error(Some(ctx), error);
+                let message = match verbosity::of(ctx) {
+                    Verbosity::Minimal => ErrorMessage::new().description(HIDDEN_DETAILS),
+                    verbosity => ErrorMessage::new().details(verbosity::describe(error, verbosity)),
+                };
+                let message = message
+                    .title("Failed to perform check")
+                    .footer(BOT_ERROR)
+                    .color(class.colour())
+                    .configure(
+                        &messages.check_failed,
+                        &Values::of(ctx).with(
+                            Placeholder::IncidentId,
+                            incident_id.clone().unwrap_or_default(),
+                        ),
+                    )
+                    .code(class.code)
+                    .incident_id(incident_id);
+
+                error!(
+                    "Check errored for {:?}: {:#}",
+                    scrub(&ctx.invocation_string()),
+                    clean_error_chain(error),
+                );
+                deliver(ctx, &message).await
             }
             None => {
-                warn!("Check failed for {:?}", ctx.invocation_string());
+                warn!("Check failed for {:?}", scrub(&ctx.invocation_string()));
+
+                None
             }
         },
-        FrameworkError::DynamicPrefix { mut error, msg, .. } => {
-            dedup_error_chain(&mut error);
-            error!("Dynamic prefix failed for {msg:?}: {error:#}");
+        FrameworkError::DynamicPrefix { error, msg, .. } => {
+            error!(
+                "Dynamic prefix failed for {}: {:#}",
+                scrub(&format!("{msg:?}")),
+                clean_error_chain(error),
+            );
+
+            None
         }
         FrameworkError::UnknownCommand {
+            ctx,
+            msg,
             prefix,
             msg_content,
+            framework,
             ..
         } => {
-            warn!("Recognized prefix {prefix:?} but did not recognize command {msg_content:?}");
+            warn!(
+                "Recognized prefix {prefix:?} but did not recognize command {:?}",
+                scrub(msg_content),
+            );
+
+            if handler.suggest_unknown_commands {
+                let suggestions =
+                    suggest::similar_commands(msg_content, &framework.options().commands);
+
+                if !suggestions.is_empty()
+                    && let Err(err) = message::send_to_channel(
+                        ctx,
+                        msg,
+                        &ErrorMessage::new()
+                            .title("Unknown command")
+                            .description(format!(
+                                "Did you mean one of the following commands?\n\n{}",
+                                suggestions
+                                    .iter()
+                                    .map(|name| {
+                                        format!(
+                                            "- {}",
+                                            usage::prefix_invocation(
+                                                prefix,
+                                                framework.bot_id,
+                                                name,
+                                            ),
+                                        )
+                                    })
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            ))
+                            .color(class.colour())
+                            .configure(
+                                &messages.unknown_command,
+                                &Values::new()
+                                    .with(
+                                        Placeholder::Command,
+                                        msg_content.split_whitespace().next().unwrap_or_default(),
+                                    )
+                                    .with(Placeholder::Prefix, *prefix)
+                                    .with(Placeholder::User, msg.author.mention().to_string()),
+                            )
+                            .code(class.code),
+                        handler.render_mode,
+                    )
+                    .await
+                {
+                    failure = Some(err.into());
+                }
+            }
+
+            None
         }
-        FrameworkError::UnknownInteraction { interaction, .. } => {
+        FrameworkError::UnknownInteraction {
+            ctx,
+            framework,
+            interaction,
+            ..
+        } => {
             warn!(
                 "Received interaction for an unknown command: {:?}",
                 interaction.data.name,
             );
+
+            // Autocomplete interactions cannot be responded to with a message.
+            if interaction.data.autocomplete().is_none()
+                && let Err(err) = message::respond_to_interaction(
+                    ctx,
+                    interaction,
+                    &ErrorMessage::new()
+                        .title("Unknown command")
+                        .description("This command is outdated or was removed.")
+                        .footer(MAYBE_BOT_ERROR)
+                        .color(class.colour())
+                        .configure(
+                            &messages.unknown_interaction,
+                            &Values::new()
+                                .with(Placeholder::Command, &interaction.data.name)
+                                .with(Placeholder::Prefix, "/")
+                                .with(Placeholder::User, interaction.user.mention().to_string()),
+                        )
+                        .code(class.code),
+                    handler.render_mode,
+                )
+                .await
+            {
+                warn!(
+                    "Failed to respond to interaction for an unknown command {:?}: {err}",
+                    interaction.data.name,
+                );
+            }
+
+            if let Some(registration) = handler.reregister_unknown_interactions {
+                match handler
+                    .registrar
+                    .register(registration, &ctx.http, &framework.options().commands)
+                    .await
+                {
+                    Ok(true) => {
+                        warn!("Registered commands again after receiving an unknown interaction")
+                    }
+                    Ok(false) => {}
+                    Err(err) => failure = Some(err.into()),
+                }
+            }
+
+            None
         }
-        other => {
+        _ => {
             warn!(
                 "Not prepared to handle unfamiliar kind of error, falling back to default `on_error` function",
             );
-            poise::builtins::on_error(other).await?;
+            poise::builtins::on_error(error).await?;
+
+            return Ok(());
         }
-    }
+    };
+
+    hooks::after_reply(&error, reply.as_ref()).await;
 
-    Ok(())
+    failure.map_or(Ok(()), Err)
 }
 
 /// Plug this into your [`poise::FrameworkOptions`] to let
@@ -563,12 +956,11 @@ pub async fn try_handle_error<U>(
 /// ```
 pub fn on_error<U>(error: FrameworkError<'_, U, anyhow::Error>) -> BoxFuture<'_, ()>
 where
-    U: Send + Sync,
+    U: Send + Sync + 'static,
 {
     Box::pin(async move {
-        if let Err(mut err) = try_handle_error(error).await {
-            dedup_error_chain(&mut err);
-            error!("Failed to handle error: {err:#}");
+        if let Err(err) = try_handle_error(error).await {
+            error!("Failed to handle error: {:#}", clean_error_chain(&err));
         }
     })
 }
@@ -0,0 +1,971 @@
+use std::sync::atomic::Ordering;
+
+use poise::{
+    CreateReply, ReplyHandle,
+    serenity_prelude::{
+        self as serenity, ChannelId, Colour, CommandInteraction, CreateAllowedMentions,
+        CreateAttachment, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+        CreateInteractionResponseMessage, CreateMessage, Http, InteractionId, Message,
+        MessageFlags, Timestamp,
+    },
+};
+use serde_json::{Value, json};
+use tracing::{error, warn};
+
+use crate::{
+    Context, ErrorHandler, ErrorPolicy, MessageConfig, Visibility, policy,
+    scrub::scrub,
+    template::{self, Values},
+};
+
+/// Message flag marking a message as using Components V2.
+const IS_COMPONENTS_V2: u64 = 1 << 15;
+
+/// Message flag marking a message as ephemeral.
+const EPHEMERAL: u64 = 1 << 6;
+
+/// Maximum length of an embed's title.
+const MAX_TITLE_LENGTH: usize = 256;
+
+/// Maximum length of an embed's description.
+const MAX_DESCRIPTION_LENGTH: usize = 4096;
+
+/// Maximum length of an embed field's name.
+const MAX_FIELD_NAME_LENGTH: usize = 256;
+
+/// Maximum length of an embed field's value.
+const MAX_FIELD_VALUE_LENGTH: usize = 1024;
+
+/// Maximum length of an embed's footer.
+const MAX_FOOTER_LENGTH: usize = 2048;
+
+/// Maximum length of all text in an embed combined.
+const MAX_EMBED_LENGTH: usize = 6000;
+
+/// Maximum length of a message's content.
+const MAX_CONTENT_LENGTH: usize = 2000;
+
+/// Maximum length of all text in a Components V2 message combined.
+const MAX_COMPONENTS_V2_LENGTH: usize = 4000;
+
+/// Maximum length of details shown inline. Longer details are truncated and
+/// attached in full as a file.
+const MAX_DETAILS_LENGTH: usize = 900;
+
+/// Name of the file longer details are attached as.
+const DETAILS_FILE_NAME: &str = "error.txt";
+
+/// How long an interaction's token can be used to respond to it, in seconds.
+const INTERACTION_TOKEN_LIFETIME: i64 = 15 * 60;
+
+/// How [`poise_error`][crate] renders the messages it presents to users.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Renders messages as embeds.
+    #[default]
+    Embed,
+    /// Renders messages as plain Markdown text, which does not require the
+    /// Embed Links permission.
+    Text,
+    /// Renders messages as [Components V2] containers.
+    ///
+    /// The subcommand picker (see [`ErrorHandler::pick_subcommands`]) is
+    /// rendered as an embed in this mode, since [`poise`] cannot send
+    /// Components V2 alongside its own components.
+    ///
+    /// [Components V2]: https://discord.com/developers/docs/components/overview
+    ComponentsV2,
+}
+
+/// How [`poise_error`][crate] replies to a slash command which deferred its
+/// response (with [`poise::Context::defer`] or similar) before failing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeferredReply {
+    /// Replaces the deferred response with the message, unless the response is
+    /// public and the message should be ephemeral, in which case the deferred
+    /// response is deleted and the message is sent as an ephemeral follow-up.
+    #[default]
+    Auto,
+    /// Always replaces the deferred response with the message, which is then
+    /// only ephemeral if the response was deferred ephemerally.
+    Edit,
+    /// Always deletes the deferred response and sends the message as a
+    /// follow-up.
+    Replace,
+}
+
+/// A field's name, value and whether it is shown inline.
+type Field = (String, String, bool);
+
+/// A message presented to a user when an error occurs.
+///
+/// Built like a [`CreateEmbed`], but can be rendered in other ways when an
+/// embed cannot be sent. Everything added to it is scrubbed of secrets (see
+/// [`Scrubber`][crate::Scrubber]).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ErrorMessage {
+    title: String,
+    description: String,
+    details: Option<String>,
+    footer: Option<String>,
+    colour: Colour,
+    fields: Vec<Field>,
+    visibility: Option<Visibility>,
+}
+
+impl ErrorMessage {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = sanitize(&title.into());
+        self
+    }
+
+    pub(crate) fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = sanitize(&description.into());
+        self
+    }
+
+    /// Sets text shown in a code block after the description, such as an error
+    /// chain.
+    pub(crate) fn details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(sanitize(&details.into()));
+        self
+    }
+
+    pub(crate) fn footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = Some(sanitize(&footer.into()));
+        self
+    }
+
+    pub(crate) fn color(mut self, colour: impl Into<Colour>) -> Self {
+        self.colour = colour.into();
+        self
+    }
+
+    /// Appends the ID of a recorded incident, if any, to the footer.
+    pub(crate) fn incident_id(mut self, incident_id: Option<String>) -> Self {
+        if let Some(incident_id) = incident_id
+            && !self
+                .footer
+                .as_ref()
+                .is_some_and(|footer| footer.contains(&incident_id))
+        {
+            self.footer = Some(match self.footer {
+                Some(footer) => format!("{footer}\nIncident ID: {incident_id}"),
+                None => format!("Incident ID: {incident_id}"),
+            });
+        }
+
+        self
+    }
+
+    /// Appends a stable error code to the footer (see
+    /// [`ErrorCatalog`][crate::ErrorCatalog]).
+    pub(crate) fn code(mut self, code: &str) -> Self {
+        self.footer = Some(match self.footer {
+            Some(footer) => format!("{footer}\nError code: {code}"),
+            None => format!("Error code: {code}"),
+        });
+        self
+    }
+
+    pub(crate) fn visibility(mut self, visibility: Visibility) -> Self {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    /// Overrides this message's wording and styling with `config`, filling in
+    /// placeholders with `values`.
+    pub(crate) fn configure(mut self, config: &MessageConfig, values: &Values) -> Self {
+        if let Some(title) = &config.title {
+            self = self.title(template::render(title, values));
+        }
+
+        if let Some(description) = &config.description {
+            self = self.description(template::render(description, values));
+        }
+
+        if let Some(footer) = &config.footer {
+            self = self.footer(template::render(footer, values));
+        }
+
+        if let Some(colour) = config.colour {
+            self = self.color(colour.0);
+        }
+
+        if let Some(visibility) = config.visibility {
+            self = self.visibility(visibility);
+        }
+
+        self
+    }
+
+    /// Returns whether this message should only be shown to the user who
+    /// invoked the command, unless `policy` says otherwise.
+    pub(crate) fn is_ephemeral(&self, policy: &ErrorPolicy) -> bool {
+        policy
+            .ephemeral
+            .unwrap_or(self.visibility != Some(Visibility::Public))
+    }
+
+    /// Returns whether this message should not be sent at all, unless `policy`
+    /// says otherwise.
+    pub(crate) fn is_silent(&self, policy: &ErrorPolicy) -> bool {
+        policy
+            .silent
+            .unwrap_or(self.visibility == Some(Visibility::Silent))
+    }
+
+    pub(crate) fn field(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+        inline: bool,
+    ) -> Self {
+        self.fields
+            .push((sanitize(&name.into()), sanitize(&value.into()), inline));
+        self
+    }
+
+    /// Returns the description followed by the details in a code block,
+    /// truncated to `max_length`.
+    fn full_description(&self, max_length: usize) -> String {
+        let Some(details) = &self.details else {
+            return truncate(&self.description, max_length);
+        };
+        let separator = if self.description.is_empty() {
+            ""
+        } else {
+            "\n\n"
+        };
+        let details = truncate(
+            &escape_code_fences(details),
+            MAX_DETAILS_LENGTH.min(max_length.saturating_sub(length("```\n\n```"))),
+        );
+        let details = format!("```\n{details}\n```");
+        let description = truncate(
+            &self.description,
+            max_length.saturating_sub(length(separator) + length(&details)),
+        );
+
+        truncate(&format!("{description}{separator}{details}"), max_length)
+    }
+
+    /// Returns the details as a file, if they are too long to be shown in full.
+    pub(crate) fn attachment(&self) -> Option<CreateAttachment> {
+        self.details
+            .as_ref()
+            .filter(|details| length(&escape_code_fences(details)) > MAX_DETAILS_LENGTH)
+            .map(|details| CreateAttachment::bytes(details.as_bytes(), DETAILS_FILE_NAME))
+    }
+
+    /// Renders this message as an embed.
+    pub(crate) fn to_embed(&self) -> CreateEmbed {
+        let (title, description, fields, footer) = self.embed_parts();
+        let mut embed = CreateEmbed::new()
+            .title(title)
+            .description(description)
+            .color(self.colour)
+            .fields(fields);
+
+        if let Some(footer) = footer {
+            embed = embed.footer(CreateEmbedFooter::new(footer));
+        }
+
+        embed
+    }
+
+    /// Returns the title, description, fields and footer of this message as an
+    /// embed, shortened to fit within Discord's limits on each of them and on
+    /// the whole embed.
+    ///
+    /// The title and footer are kept whole first, then the description, then
+    /// as many fields as fit, shortening the last one if needed.
+    fn embed_parts(&self) -> (String, String, Vec<Field>, Option<String>) {
+        let title = truncate(&self.title, MAX_TITLE_LENGTH);
+        let footer = self
+            .footer
+            .as_ref()
+            .map(|footer| truncate(footer, MAX_FOOTER_LENGTH));
+        let mut remaining =
+            MAX_EMBED_LENGTH.saturating_sub(length(&title) + footer.as_deref().map_or(0, length));
+        let description = self.full_description(MAX_DESCRIPTION_LENGTH.min(remaining));
+
+        remaining = remaining.saturating_sub(length(&description));
+
+        let mut fields = Vec::new();
+
+        for (name, value, inline) in &self.fields {
+            let name = truncate(name, MAX_FIELD_NAME_LENGTH);
+
+            // Fields cannot have an empty value.
+            if length(&name) >= remaining {
+                break;
+            }
+
+            remaining -= length(&name);
+
+            let value = truncate(value, MAX_FIELD_VALUE_LENGTH.min(remaining));
+
+            remaining -= length(&value);
+            fields.push((name, value, *inline));
+        }
+
+        (title, description, fields, footer)
+    }
+
+    /// Renders this message as a Components V2 container.
+    fn to_components_v2(&self) -> Value {
+        let mut components = vec![json!({
+            "type": 10,
+            "content": format!(
+                "### {}\n{}",
+                truncate(&self.title, MAX_TITLE_LENGTH),
+                self.full_description(MAX_DESCRIPTION_LENGTH / 2),
+            ),
+        })];
+
+        for (name, value, _) in &self.fields {
+            components.push(json!({
+                "type": 10,
+                "content": format!(
+                    "**{}**\n{}",
+                    truncate(name, MAX_FIELD_NAME_LENGTH),
+                    truncate(value, MAX_FIELD_VALUE_LENGTH),
+                ),
+            }));
+        }
+
+        if let Some(footer) = &self.footer {
+            components.push(json!({ "type": 14 }));
+            components.push(json!({
+                "type": 10,
+                "content": format!("-# {}", truncate(footer, MAX_FOOTER_LENGTH)),
+            }));
+        }
+
+        if self.attachment().is_some() {
+            components.push(json!({
+                "type": 13,
+                "file": { "url": format!("attachment://{DETAILS_FILE_NAME}") },
+            }));
+        }
+
+        let mut remaining = MAX_COMPONENTS_V2_LENGTH;
+
+        for component in &mut components {
+            if let Some(content) = component.get_mut("content") {
+                let truncated = truncate(content.as_str().unwrap(), remaining);
+
+                remaining = remaining.saturating_sub(length(&truncated));
+                *content = Value::String(truncated);
+            }
+        }
+
+        json!([{
+            "type": 17,
+            "accent_color": self.colour.0,
+            "components": components,
+        }])
+    }
+
+    /// Renders this message as a Components V2 message body.
+    fn to_components_v2_body(&self, ephemeral: bool) -> Value {
+        let mut flags = IS_COMPONENTS_V2;
+
+        if ephemeral {
+            flags |= EPHEMERAL;
+        }
+
+        json!({
+            "flags": flags,
+            "components": self.to_components_v2(),
+            "allowed_mentions": { "parse": [] },
+        })
+    }
+
+    /// Renders this message as the body of an interaction response, follow-up
+    /// or edit, rendered in `render_mode`.
+    fn to_body(&self, render_mode: RenderMode, ephemeral: bool) -> Value {
+        let mut body = match render_mode {
+            RenderMode::Embed => json!({
+                "content": "",
+                "embeds": [self.to_embed()],
+                "allowed_mentions": { "parse": [] },
+            }),
+            RenderMode::Text => json!({
+                "content": self.to_text(),
+                "embeds": [],
+                "allowed_mentions": { "parse": [] },
+            }),
+            RenderMode::ComponentsV2 => return self.to_components_v2_body(ephemeral),
+        };
+
+        if ephemeral {
+            body["flags"] = json!(EPHEMERAL);
+        }
+
+        body
+    }
+
+    /// Renders this message as Markdown text.
+    pub(crate) fn to_text(&self) -> String {
+        let mut text = format!(
+            "**{}**\n{}",
+            truncate(&self.title, MAX_TITLE_LENGTH),
+            self.full_description(MAX_CONTENT_LENGTH / 2),
+        );
+
+        for (name, value, _) in &self.fields {
+            text.push_str(&format!("\n\n**{name}**\n{value}"));
+        }
+
+        if let Some(footer) = &self.footer {
+            text.push_str(&format!("\n\n-# {footer}"));
+        }
+
+        truncate(&text, MAX_CONTENT_LENGTH)
+    }
+
+    /// Adds this message's attachment, if any, to `reply` and stops it from
+    /// mentioning anyone.
+    pub(crate) fn prepare_reply(&self, reply: CreateReply) -> CreateReply {
+        let reply = reply.allowed_mentions(CreateAllowedMentions::new());
+
+        match self.attachment() {
+            Some(attachment) => reply.attachment(attachment),
+            None => reply,
+        }
+    }
+
+    /// Returns this message's attachment, if any, as a list of files.
+    fn files(&self) -> Vec<CreateAttachment> {
+        self.attachment().into_iter().collect()
+    }
+}
+
+/// Removes secrets and breaks up mentions which would notify many users in
+/// `text`.
+fn sanitize(text: &str) -> String {
+    escape_mentions(&scrub(text))
+}
+
+/// Breaks up `@everyone`, `@here` and role mentions in `text` with zero-width
+/// spaces, so that they are shown as plain text.
+///
+/// Replies never mention anyone regardless, but this keeps them from appearing
+/// to.
+fn escape_mentions(text: &str) -> String {
+    text.replace("@everyone", "@\u{200B}everyone")
+        .replace("@here", "@\u{200B}here")
+        .replace("<@&", "<@\u{200B}&")
+}
+
+/// Returns the length of `text` as counted by Discord.
+fn length(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Shortens `text` to at most `max_length` characters, ending it with an
+/// ellipsis if anything was removed.
+fn truncate(text: &str, max_length: usize) -> String {
+    if length(text) <= max_length {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(max_length.saturating_sub(1)).collect();
+
+    if max_length > 0 {
+        truncated.push('…');
+    }
+
+    truncated
+}
+
+/// Breaks up code fences in `text` with zero-width spaces, so that it cannot
+/// end the code block it is shown in.
+fn escape_code_fences(text: &str) -> String {
+    text.replace("```", "`\u{200B}`\u{200B}`")
+}
+
+/// Sends `message` in response to the invocation in `ctx`, rendered according
+/// to the installed [`ErrorHandler`].
+///
+/// If the message cannot be sent that way, it is sent as plain text without
+/// referencing the invoking message, unless that would be the same as the
+/// first attempt. If that fails too, it is sent to the invoking user's DMs, and
+/// if even that fails, it is only logged. Every failed attempt is logged.
+///
+/// Nothing is sent if the message or the invoked command's [`ErrorPolicy`] is
+/// silent, and the reply is ephemeral unless either of them is public, with the
+/// policy taking precedence.
+///
+/// Returns a handle to the reply, unless it was sent with Components V2, to
+/// DMs or not at all.
+pub(crate) async fn deliver<'a, U>(
+    ctx: Context<'a, U>,
+    message: &ErrorMessage,
+) -> Option<ReplyHandle<'a>> {
+    let policy = policy::of(ctx);
+
+    if message.is_silent(&policy) {
+        return None;
+    }
+
+    let invocation_string = scrub(&ctx.invocation_string());
+    let render_mode = ErrorHandler::get().render_mode;
+    let ephemeral = message.is_ephemeral(&policy);
+    let mut err = match send(ctx, message, render_mode, ephemeral, true).await {
+        Ok(reply) => return reply,
+        Err(err) => err,
+    };
+
+    // A plain text reply to an interaction would be the same as the first
+    // attempt, since interaction responses never reference a message.
+    if render_mode != RenderMode::Text || matches!(ctx, poise::Context::Prefix(_)) {
+        warn!(
+            "Failed to send error message for {invocation_string:?} in {render_mode:?} mode, falling back to plain text: {err}",
+        );
+
+        err = match send(ctx, message, RenderMode::Text, ephemeral, false).await {
+            Ok(reply) => return reply,
+            Err(err) => err,
+        };
+    }
+
+    warn!(
+        "Failed to send error message for {invocation_string:?} as plain text, falling back to DMs: {err}",
+    );
+
+    let Err(err) = ctx
+        .author()
+        .direct_message(
+            ctx.serenity_context(),
+            CreateMessage::new()
+                .content(message.to_text())
+                .allowed_mentions(CreateAllowedMentions::new())
+                .add_files(message.files()),
+        )
+        .await
+    else {
+        return None;
+    };
+
+    error!(
+        "Failed to send error message for {invocation_string:?} in DMs, giving up: {err}\n{}",
+        message.to_text(),
+    );
+
+    None
+}
+
+/// Sends `message` in response to the component or modal interaction with `id`
+/// and `token`, which happened during the invocation in `ctx`, or as a
+/// follow-up if the interaction has already been responded to.
+///
+/// Falls back to [`deliver`] if neither can be sent. Every failed attempt is
+/// logged.
+pub(crate) async fn deliver_to_interaction<U>(
+    ctx: Context<'_, U>,
+    id: InteractionId,
+    token: &str,
+    message: &ErrorMessage,
+) {
+    let policy = policy::of(ctx);
+
+    if message.is_silent(&policy) {
+        return;
+    }
+
+    let http = &ctx.serenity_context().http;
+    let render_mode = ErrorHandler::get().render_mode;
+    let body = message.to_body(render_mode, message.is_ephemeral(&policy));
+
+    let invocation_string = scrub(&ctx.invocation_string());
+    let Err(err) = http
+        .create_interaction_response(
+            id,
+            token,
+            &json!({ "type": 4, "data": body }),
+            message.files(),
+        )
+        .await
+    else {
+        return;
+    };
+
+    warn!(
+        "Failed to respond to interaction {id} during {invocation_string:?}, falling back to a follow-up: {err}",
+    );
+
+    let Err(err) = http
+        .create_followup_message(token, &body, message.files())
+        .await
+    else {
+        return;
+    };
+
+    warn!(
+        "Failed to send follow-up to interaction {id} during {invocation_string:?}, falling back to replying to the command: {err}",
+    );
+    deliver(ctx, message).await;
+}
+
+/// Sends `message` as a reply to the invocation in `ctx`, rendered in
+/// `render_mode`, referencing the invoking message if `reference` is true and
+/// the invocation is a prefix command.
+///
+/// If the invocation is a slash command which deferred its response, the
+/// deferred response is replaced according to the installed handler's
+/// [`DeferredReply`]. If it is too late to respond to the interaction, the
+/// message is sent to its channel instead, unless it should be ephemeral, in
+/// which case an error is returned.
+///
+/// Returns a handle to the reply, unless it was sent with Components V2,
+/// replaced a deferred response or was sent to the channel.
+async fn send<'a, U>(
+    ctx: Context<'a, U>,
+    message: &ErrorMessage,
+    render_mode: RenderMode,
+    ephemeral: bool,
+    reference: bool,
+) -> Result<Option<ReplyHandle<'a>>, serenity::Error> {
+    if let poise::Context::Application(app) = ctx {
+        let http = &ctx.serenity_context().http;
+
+        if is_expired(app.interaction) {
+            if ephemeral {
+                return Err(serenity::Error::Other(
+                    "the interaction has expired and the message cannot be sent publicly",
+                ));
+            }
+
+            send_to(
+                ctx.serenity_context(),
+                app.interaction.channel_id,
+                None,
+                message,
+                render_mode,
+            )
+            .await?;
+
+            return Ok(None);
+        }
+
+        if app.has_sent_initial_response.load(Ordering::SeqCst)
+            && let Some(deferred_ephemerally) = deferred_response(http, app.interaction).await
+        {
+            let edit = match ErrorHandler::get().deferred_reply {
+                DeferredReply::Auto => deferred_ephemerally || !ephemeral,
+                DeferredReply::Edit => true,
+                DeferredReply::Replace => false,
+            };
+
+            if edit {
+                http.edit_original_interaction_response(
+                    &app.interaction.token,
+                    &message.to_body(render_mode, false),
+                    message.files(),
+                )
+                .await?;
+
+                return Ok(None);
+            }
+
+            app.interaction.delete_response(http).await?;
+        }
+    }
+
+    let reply = message.prepare_reply(CreateReply::default().reply(reference).ephemeral(ephemeral));
+
+    match render_mode {
+        RenderMode::Embed => Ok(Some(ctx.send(reply.embed(message.to_embed())).await?)),
+        RenderMode::Text => Ok(Some(ctx.send(reply.content(message.to_text())).await?)),
+        RenderMode::ComponentsV2 => {
+            let http = &ctx.serenity_context().http;
+
+            match ctx {
+                poise::Context::Application(ctx) => {
+                    let body = message.to_components_v2_body(ephemeral);
+
+                    if ctx.has_sent_initial_response.load(Ordering::SeqCst) {
+                        http.create_followup_message(
+                            &ctx.interaction.token,
+                            &body,
+                            message.files(),
+                        )
+                        .await?;
+                    } else {
+                        http.create_interaction_response(
+                            ctx.interaction.id,
+                            &ctx.interaction.token,
+                            &json!({ "type": 4, "data": body }),
+                            message.files(),
+                        )
+                        .await?;
+                        ctx.has_sent_initial_response.store(true, Ordering::SeqCst);
+                    }
+                }
+                poise::Context::Prefix(ctx) => {
+                    send_to(
+                        ctx.serenity_context,
+                        ctx.msg.channel_id,
+                        reference.then_some(ctx.msg),
+                        message,
+                        render_mode,
+                    )
+                    .await?;
+                }
+            }
+
+            Ok(None)
+        }
+    }
+}
+
+/// Returns whether it is too late to respond to `interaction`, since its
+/// token has expired.
+fn is_expired(interaction: &CommandInteraction) -> bool {
+    Timestamp::now().unix_timestamp() - interaction.id.created_at().unix_timestamp()
+        >= INTERACTION_TOKEN_LIFETIME
+}
+
+/// Returns whether `interaction`'s response is still deferred, showing that
+/// the bot is thinking, and if so, whether it was deferred ephemerally.
+async fn deferred_response(http: &Http, interaction: &CommandInteraction) -> Option<bool> {
+    let flags = interaction.get_response(http).await.ok()?.flags?;
+
+    flags
+        .contains(MessageFlags::LOADING)
+        .then_some(flags.contains(MessageFlags::EPHEMERAL))
+}
+
+/// Sends `message` as a reply to `msg`, rendered in `render_mode`.
+pub(crate) async fn send_to_channel(
+    ctx: &serenity::Context,
+    msg: &Message,
+    message: &ErrorMessage,
+    render_mode: RenderMode,
+) -> Result<(), serenity::Error> {
+    if message.is_silent(&ErrorPolicy::new()) {
+        return Ok(());
+    }
+
+    send_to(ctx, msg.channel_id, Some(msg), message, render_mode).await
+}
+
+/// Sends `message` to `channel_id`, replying to `reference` if given, rendered
+/// in `render_mode`.
+async fn send_to(
+    ctx: &serenity::Context,
+    channel_id: ChannelId,
+    reference: Option<&Message>,
+    message: &ErrorMessage,
+    render_mode: RenderMode,
+) -> Result<(), serenity::Error> {
+    let mut create_message = CreateMessage::new()
+        .allowed_mentions(CreateAllowedMentions::new())
+        .add_files(message.files());
+
+    if let Some(reference) = reference {
+        create_message = create_message.reference_message(reference);
+    }
+
+    match render_mode {
+        RenderMode::Embed => {
+            channel_id
+                .send_message(ctx, create_message.embed(message.to_embed()))
+                .await?;
+        }
+        RenderMode::Text => {
+            channel_id
+                .send_message(ctx, create_message.content(message.to_text()))
+                .await?;
+        }
+        RenderMode::ComponentsV2 => {
+            let mut body = message.to_components_v2_body(false);
+
+            if let Some(reference) = reference {
+                body["message_reference"] = json!({
+                    "message_id": reference.id,
+                    "fail_if_not_exists": false,
+                });
+            }
+
+            ctx.http
+                .send_message(channel_id, message.files(), &body)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Responds to `interaction` with `message`, rendered in `render_mode`.
+pub(crate) async fn respond_to_interaction(
+    ctx: &serenity::Context,
+    interaction: &CommandInteraction,
+    message: &ErrorMessage,
+    render_mode: RenderMode,
+) -> Result<(), serenity::Error> {
+    if message.is_silent(&ErrorPolicy::new()) {
+        return Ok(());
+    }
+
+    let ephemeral = message.is_ephemeral(&ErrorPolicy::new());
+    let response = CreateInteractionResponseMessage::new()
+        .ephemeral(ephemeral)
+        .allowed_mentions(CreateAllowedMentions::new())
+        .add_files(message.files());
+
+    match render_mode {
+        RenderMode::Embed => {
+            interaction
+                .create_response(
+                    ctx,
+                    CreateInteractionResponse::Message(response.embed(message.to_embed())),
+                )
+                .await?;
+        }
+        RenderMode::Text => {
+            interaction
+                .create_response(
+                    ctx,
+                    CreateInteractionResponse::Message(response.content(message.to_text())),
+                )
+                .await?;
+        }
+        RenderMode::ComponentsV2 => {
+            ctx.http
+                .create_interaction_response(
+                    interaction.id,
+                    &interaction.token,
+                    &json!({ "type": 4, "data": message.to_components_v2_body(ephemeral) }),
+                    message.files(),
+                )
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the length of all text in `message` rendered as an embed.
+    fn embed_length(message: &ErrorMessage) -> usize {
+        let (title, description, fields, footer) = message.embed_parts();
+
+        length(&title)
+            + length(&description)
+            + fields
+                .iter()
+                .map(|(name, value, _)| length(name) + length(value))
+                .sum::<usize>()
+            + footer.as_deref().map_or(0, length)
+    }
+
+    #[test]
+    fn truncate_keeps_short_text() {
+        assert_eq!(truncate("abcd", 4), "abcd");
+        assert_eq!(truncate("", 0), "");
+    }
+
+    #[test]
+    fn truncate_ends_with_ellipsis() {
+        assert_eq!(truncate("abcdef", 4), "abc…");
+        assert_eq!(truncate("abcdef", 1), "…");
+        assert_eq!(truncate("abcdef", 0), "");
+        assert_eq!(truncate("ééééé", 3), "éé…");
+    }
+
+    #[test]
+    fn escape_code_fences_breaks_up_runs_of_backticks() {
+        for run in [3, 4, 6] {
+            let escaped = escape_code_fences(&format!("a{}b", "`".repeat(run)));
+
+            assert!(!escaped.contains("```"), "{run} backticks: {escaped:?}");
+            assert_eq!(escaped.matches('`').count(), run);
+        }
+    }
+
+    #[test]
+    fn full_description_closes_code_block() {
+        let message = ErrorMessage::new()
+            .description("Something went wrong")
+            .details(format!("{}```", "x".repeat(2000)));
+        let description = message.full_description(MAX_DESCRIPTION_LENGTH);
+
+        assert!(description.starts_with("Something went wrong\n\n```\n"));
+        assert!(description.ends_with("…\n```"));
+        assert_eq!(description.matches("```").count(), 2);
+
+        for max_length in [0, 5, 20, 100] {
+            assert!(length(&message.full_description(max_length)) <= max_length);
+        }
+    }
+
+    #[test]
+    fn attachment_is_added_once_details_are_too_long() {
+        let details = |length: usize| ErrorMessage::new().details("x".repeat(length));
+
+        assert!(details(MAX_DETAILS_LENGTH).attachment().is_none());
+        assert!(details(MAX_DETAILS_LENGTH + 1).attachment().is_some());
+
+        // Escaping code fences makes details longer.
+        let fences = ErrorMessage::new().details("```".repeat(MAX_DETAILS_LENGTH / 3));
+
+        assert!(fences.attachment().is_some());
+    }
+
+    #[test]
+    fn embed_fits_within_total_limit() {
+        let mut message = ErrorMessage::new()
+            .title("t".repeat(1000))
+            .description("d".repeat(5000))
+            .details("x".repeat(5000))
+            .footer("f".repeat(100));
+
+        for _ in 0..10 {
+            message = message.field("n".repeat(300), "v".repeat(2000), false);
+        }
+
+        let (title, _, fields, footer) = message.embed_parts();
+
+        assert!(embed_length(&message) <= MAX_EMBED_LENGTH);
+        assert_eq!(length(&title), MAX_TITLE_LENGTH);
+        assert_eq!(footer.as_deref().map(length), Some(100));
+        assert!(!fields.is_empty());
+        assert!(fields.iter().all(|(name, value, _)| {
+            !name.is_empty()
+                && !value.is_empty()
+                && length(name) <= MAX_FIELD_NAME_LENGTH
+                && length(value) <= MAX_FIELD_VALUE_LENGTH
+        }));
+
+        let message = message.footer("f".repeat(3000));
+
+        assert!(embed_length(&message) <= MAX_EMBED_LENGTH);
+    }
+
+    #[test]
+    fn embed_keeps_short_messages_whole() {
+        let message = ErrorMessage::new()
+            .title("Failed to parse argument")
+            .description("Failed to parse \"abc\" into an argument")
+            .field("Usage", "`/buy <amount>`", false)
+            .footer("Error code: PE-ARGUMENT");
+        let (_, description, fields, _) = message.embed_parts();
+
+        assert_eq!(description, "Failed to parse \"abc\" into an argument");
+        assert_eq!(
+            fields,
+            [("Usage".to_string(), "`/buy <amount>`".to_string(), false)],
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use poise::{Command, Context};
+
+use crate::Verbosity;
+
+/// How errors from a particular command and its subcommands are handled,
+/// overriding the installed [`ErrorHandler`][crate::ErrorHandler].
+///
+/// Attached to a command with [`ErrorPolicy::apply`] or through
+/// [`Command::custom_data`]. Options which a subcommand's policy leaves unset
+/// are taken from its parent commands' policies.
+///
+/// # Examples
+///
+/// ```
+/// use poise_error::{ErrorPolicy, Verbosity};
+///
+/// /// Buys an item
+/// #[poise::command(slash_command)]
+/// async fn buy(ctx: poise_error::Context<'_>) -> anyhow::Result<()> {
+///     Ok(())
+/// }
+///
+/// /// Greets everyone
+/// #[poise::command(
+///     slash_command,
+///     custom_data = "ErrorPolicy::new().ephemeral(false)"
+/// )]
+/// async fn greet(ctx: poise_error::Context<'_>) -> anyhow::Result<()> {
+///     Ok(())
+/// }
+///
+/// let commands = vec![
+///     ErrorPolicy::new()
+///         .verbosity(Verbosity::Minimal)
+///         .internal_error_description("Your purchase failed. You have not been charged.")
+///         .apply(buy()),
+///     greet(),
+/// ];
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorPolicy {
+    pub(crate) ephemeral: Option<bool>,
+    pub(crate) silent: Option<bool>,
+    pub(crate) verbosity: Option<Verbosity>,
+    pub(crate) record_incidents: Option<bool>,
+    pub(crate) user_error_title: Option<String>,
+    pub(crate) internal_error_title: Option<String>,
+    pub(crate) internal_error_description: Option<String>,
+}
+
+impl ErrorPolicy {
+    /// Creates an [`ErrorPolicy`] which overrides nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether error messages are only shown to the user who invoked the
+    /// command. Only affects slash commands.
+    ///
+    /// Defaults to `true`.
+    pub fn ephemeral(mut self, ephemeral: bool) -> Self {
+        self.ephemeral = Some(ephemeral);
+        self
+    }
+
+    /// Sets whether errors are only logged, without replying to the user.
+    ///
+    /// Defaults to `false`.
+    pub fn silent(mut self, silent: bool) -> Self {
+        self.silent = Some(silent);
+        self
+    }
+
+    /// Sets how much detail about errors is shown to users, other than those
+    /// in the verbose audience (see
+    /// [`ErrorHandler::verbose_audience`][crate::ErrorHandler::verbose_audience]).
+    ///
+    /// Defaults to the handler's verbosity.
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = Some(verbosity);
+        self
+    }
+
+    /// Sets whether internal errors are recorded in the handler's incident
+    /// store (see
+    /// [`ErrorHandler::incident_store`][crate::ErrorHandler::incident_store]).
+    ///
+    /// Defaults to `true`.
+    pub fn record_incidents(mut self, record_incidents: bool) -> Self {
+        self.record_incidents = Some(record_incidents);
+        self
+    }
+
+    /// Sets the title of messages about user errors.
+    pub fn user_error_title(mut self, title: impl Into<String>) -> Self {
+        self.user_error_title = Some(title.into());
+        self
+    }
+
+    /// Sets the title of messages about internal errors.
+    pub fn internal_error_title(mut self, title: impl Into<String>) -> Self {
+        self.internal_error_title = Some(title.into());
+        self
+    }
+
+    /// Sets text shown above the details of internal errors.
+    pub fn internal_error_description(mut self, description: impl Into<String>) -> Self {
+        self.internal_error_description = Some(description.into());
+        self
+    }
+
+    /// Attaches this policy to `command`, replacing its
+    /// [`Command::custom_data`].
+    pub fn apply<U, E>(self, mut command: Command<U, E>) -> Command<U, E> {
+        command.custom_data = Box::new(self);
+        command
+    }
+
+    /// Fills in options this policy leaves unset from `parent`.
+    fn inherit(self, parent: &Self) -> Self {
+        Self {
+            ephemeral: self.ephemeral.or(parent.ephemeral),
+            silent: self.silent.or(parent.silent),
+            verbosity: self.verbosity.or(parent.verbosity),
+            record_incidents: self.record_incidents.or(parent.record_incidents),
+            user_error_title: self
+                .user_error_title
+                .or_else(|| parent.user_error_title.clone()),
+            internal_error_title: self
+                .internal_error_title
+                .or_else(|| parent.internal_error_title.clone()),
+            internal_error_description: self
+                .internal_error_description
+                .or_else(|| parent.internal_error_description.clone()),
+        }
+    }
+}
+
+/// Returns the policy for the command invoked in `ctx`, combined with the
+/// policies of its parent commands.
+pub(crate) fn of<U, E>(ctx: Context<'_, U, E>) -> ErrorPolicy {
+    std::iter::once(ctx.command())
+        .chain(ctx.parent_commands().iter().rev().copied())
+        .filter_map(|command| command.custom_data.downcast_ref::<ErrorPolicy>())
+        .fold(ErrorPolicy::new(), |policy, parent| policy.inherit(parent))
+}
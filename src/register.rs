@@ -0,0 +1,81 @@
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use poise::{
+    Command,
+    serenity_prelude::{self as serenity, GuildId, Http},
+};
+
+/// Minimum amount of time between two registrations.
+const DEBOUNCE: Duration = Duration::from_secs(60);
+
+/// Where [`poise_error`][crate] registers commands when it registers them
+/// again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Registration {
+    /// Registers commands globally, see
+    /// [`poise::builtins::register_globally`].
+    Global,
+    /// Registers commands in a single guild, see
+    /// [`poise::builtins::register_in_guild`].
+    Guild(GuildId),
+}
+
+/// Keeps track of registrations so that they are not repeated too often.
+#[derive(Debug, Default)]
+pub(crate) struct Registrar {
+    running: AtomicBool,
+    last: Mutex<Option<Instant>>,
+}
+
+impl Registrar {
+    /// Registers `commands` again, unless a registration is already running or
+    /// one has finished recently.
+    ///
+    /// Returns whether commands were registered.
+    pub(crate) async fn register<U, E>(
+        &self,
+        registration: Registration,
+        http: &Http,
+        commands: &[Command<U, E>],
+    ) -> Result<bool, serenity::Error> {
+        if self
+            .last
+            .lock()
+            .unwrap()
+            .is_some_and(|last| last.elapsed() < DEBOUNCE)
+        {
+            return Ok(false);
+        }
+
+        if self.running.swap(true, Ordering::AcqRel) {
+            return Ok(false);
+        }
+
+        let _guard = RunningGuard(&self.running);
+        let result = match registration {
+            Registration::Global => poise::builtins::register_globally(http, commands).await,
+            Registration::Guild(guild_id) => {
+                poise::builtins::register_in_guild(http, commands, guild_id).await
+            }
+        };
+
+        *self.last.lock().unwrap() = Some(Instant::now());
+
+        result.map(|()| true)
+    }
+}
+
+/// Marks a registration as finished when dropped, even if it was cancelled.
+struct RunningGuard<'a>(&'a AtomicBool);
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
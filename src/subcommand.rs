@@ -0,0 +1,243 @@
+use std::time::Duration;
+
+use poise::{
+    ApplicationContext, Command, CreateReply, FrameworkError, PrefixContext, ReplyHandle,
+    serenity_prelude::{
+        ButtonStyle, ComponentInteractionCollector, ComponentInteractionDataKind, CreateActionRow,
+        CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage,
+        CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption,
+    },
+};
+
+use tracing::warn;
+
+use crate::{
+    Context, ErrorClass, ErrorHandler,
+    message::{ErrorMessage, RenderMode, deliver},
+    policy, usage,
+};
+
+/// Maximum number of subcommands to present as buttons instead of a select
+/// menu.
+const MAX_BUTTONS: usize = 5;
+
+/// Maximum number of options in a select menu.
+const MAX_OPTIONS: usize = 25;
+
+/// Maximum length of a select menu option's description.
+const MAX_DESCRIPTION_LENGTH: usize = 100;
+
+/// How long to wait for a subcommand to be picked.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Returns the subcommands of the command invoked in `ctx` which can be invoked
+/// in the same way as it was.
+pub(crate) fn invocable_subcommands<'a, U>(
+    ctx: Context<'a, U>,
+) -> Vec<&'a Command<U, anyhow::Error>> {
+    ctx.command()
+        .subcommands
+        .iter()
+        .filter(|subcommand| match ctx {
+            poise::Context::Application(_) => subcommand.slash_action.is_some(),
+            poise::Context::Prefix(_) => subcommand.prefix_action.is_some(),
+        })
+        .collect()
+}
+
+/// Sends `message` along with components for picking one of the invoked
+/// command's subcommands, then either invokes the picked subcommand or, if it
+/// has required parameters, shows its usage in `class`'s colour.
+///
+/// Waits up to [`TIMEOUT`] for a subcommand to be picked, and runs it before
+/// returning. Returns a handle to the message the subcommand was picked from,
+/// if it was sent.
+pub(crate) async fn pick<'a, U>(
+    ctx: Context<'a, U>,
+    message: &ErrorMessage,
+    class: ErrorClass,
+) -> Result<Option<ReplyHandle<'a>>, anyhow::Error> {
+    let policy = policy::of(ctx);
+
+    if message.is_silent(&policy) {
+        return Ok(None);
+    }
+
+    let subcommands = invocable_subcommands(ctx);
+    let invocation_id = match ctx {
+        poise::Context::Application(ctx) => ctx.interaction.id.get(),
+        poise::Context::Prefix(ctx) => ctx.msg.id.get(),
+    };
+    let custom_id = format!("{invocation_id}-subcommand");
+    let components = if subcommands.len() <= MAX_BUTTONS {
+        vec![CreateActionRow::Buttons(
+            subcommands
+                .iter()
+                .enumerate()
+                .map(|(index, subcommand)| {
+                    CreateButton::new(format!("{custom_id}-{index}"))
+                        .label(&subcommand.name)
+                        .style(ButtonStyle::Secondary)
+                })
+                .collect(),
+        )]
+    } else {
+        if subcommands.len() > MAX_OPTIONS {
+            warn!(
+                "{:?} has {} subcommands, only the first {MAX_OPTIONS} can be picked from the select menu",
+                ctx.command().qualified_name,
+                subcommands.len(),
+            );
+        }
+
+        vec![CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                &custom_id,
+                CreateSelectMenuKind::String {
+                    options: subcommands
+                        .iter()
+                        .take(MAX_OPTIONS)
+                        .enumerate()
+                        .map(|(index, subcommand)| {
+                            let option =
+                                CreateSelectMenuOption::new(&subcommand.name, index.to_string());
+
+                            match &subcommand.description {
+                                Some(description) => option.description(
+                                    description
+                                        .chars()
+                                        .take(MAX_DESCRIPTION_LENGTH)
+                                        .collect::<String>(),
+                                ),
+                                None => option,
+                            }
+                        })
+                        .collect(),
+                },
+            )
+            .placeholder("Choose a subcommand"),
+        )]
+    };
+    let reply = message
+        .prepare_reply(CreateReply::default())
+        .components(components)
+        .reply(true)
+        .ephemeral(message.is_ephemeral(&policy));
+    let reply = match ErrorHandler::get().render_mode {
+        RenderMode::Text => reply.content(message.to_text()),
+        RenderMode::Embed | RenderMode::ComponentsV2 => reply.embed(message.to_embed()),
+    };
+    let reply = match ctx.send(reply).await {
+        Ok(reply) => reply,
+        Err(err) => {
+            warn!("Failed to send subcommand picker, falling back to a plain error message: {err}");
+
+            return Ok(deliver(ctx, message).await);
+        }
+    };
+    let filter_custom_id = custom_id.clone();
+    let interaction = ComponentInteractionCollector::new(ctx.serenity_context())
+        .author_id(ctx.author().id)
+        .channel_id(ctx.channel_id())
+        .timeout(TIMEOUT)
+        .filter(move |interaction| interaction.data.custom_id.starts_with(&filter_custom_id))
+        .await;
+    let Some(interaction) = interaction else {
+        reply
+            .edit(ctx, CreateReply::default().components(Vec::new()))
+            .await?;
+
+        return Ok(Some(reply));
+    };
+    let index = match &interaction.data.kind {
+        ComponentInteractionDataKind::StringSelect { values } => values.first().cloned(),
+        _ => interaction
+            .data
+            .custom_id
+            .strip_prefix(&format!("{custom_id}-"))
+            .map(str::to_string),
+    }
+    .and_then(|index| index.parse::<usize>().ok());
+    let Some(&subcommand) = index.and_then(|index| subcommands.get(index)) else {
+        return Ok(Some(reply));
+    };
+
+    interaction
+        .create_response(
+            ctx.serenity_context(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new().components(Vec::new()),
+            ),
+        )
+        .await?;
+
+    if subcommand
+        .parameters
+        .iter()
+        .any(|parameter| parameter.required)
+    {
+        deliver(
+            ctx,
+            &ErrorMessage::new()
+                .title(format!("Usage of `{}`", subcommand.qualified_name))
+                .description(usage::usage(ctx, subcommand))
+                .color(class.colour()),
+        )
+        .await;
+
+        return Ok(Some(reply));
+    }
+
+    let mut parent_commands = ctx.parent_commands().to_vec();
+
+    parent_commands.push(ctx.command());
+
+    let result = match ctx {
+        poise::Context::Application(ctx) => {
+            invoke_slash(ApplicationContext {
+                args: &[],
+                parent_commands: &parent_commands,
+                command: subcommand,
+                ..ctx
+            })
+            .await
+        }
+        poise::Context::Prefix(ctx) => {
+            let Some(action) = subcommand.prefix_action else {
+                return Ok(Some(reply));
+            };
+
+            poise::dispatch::run_invocation(PrefixContext {
+                invoked_command_name: &subcommand.name,
+                args: "",
+                parent_commands: &parent_commands,
+                command: subcommand,
+                action,
+                ..ctx
+            })
+            .await
+        }
+    };
+
+    if let Err(error) = result {
+        error.handle(ctx.framework().options()).await;
+    }
+
+    Ok(Some(reply))
+}
+
+/// Runs a slash command the same way [`poise`] would, including checks.
+async fn invoke_slash<U>(
+    ctx: ApplicationContext<'_, U, anyhow::Error>,
+) -> Result<(), FrameworkError<'_, U, anyhow::Error>> {
+    let Some(action) = ctx.command.slash_action else {
+        return Ok(());
+    };
+
+    poise::dispatch::check_permissions_and_cooldown(ctx.into()).await?;
+    (ctx.framework.options.pre_command)(ctx.into()).await;
+    action(ctx).await?;
+    (ctx.framework.options.post_command)(ctx.into()).await;
+
+    Ok(())
+}
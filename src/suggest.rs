@@ -0,0 +1,168 @@
+use poise::Command;
+
+/// Maximum number of suggestions to present at once.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Finds the command names and aliases, including those of subcommands, most
+/// similar to the start of `input`.
+///
+/// Names are only considered if the number of edits needed to turn them into
+/// the input is at most a third of their length (rounded down, but at least
+/// one), which catches typos without matching unrelated words.
+pub(crate) fn similar_commands<U, E>(input: &str, commands: &[Command<U, E>]) -> Vec<String> {
+    let mut names = Vec::new();
+
+    collect_names(commands, "", &mut names);
+
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let mut suggestions: Vec<(usize, String)> = names
+        .into_iter()
+        .filter_map(|name| {
+            let word_count = name.split(' ').count();
+
+            if word_count > words.len() {
+                return None;
+            }
+
+            let typed = words[..word_count].join(" ").to_lowercase();
+            let distance = strsim::levenshtein(&typed, &name.to_lowercase());
+
+            (distance <= max_distance(&name)).then_some((distance, name))
+        })
+        .collect();
+
+    suggestions.sort();
+    suggestions.dedup_by(|(_, a), (_, b)| a == b);
+    suggestions.truncate(MAX_SUGGESTIONS);
+
+    suggestions.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Finds the string in `candidates` most similar to `input`, if any are close
+/// enough by the same measure as [`similar_commands`].
+pub(crate) fn closest<'a>(
+    input: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let input = input.to_lowercase();
+
+    candidates
+        .into_iter()
+        .map(|candidate| {
+            (
+                strsim::levenshtein(&input, &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, candidate)| *distance <= max_distance(candidate))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn max_distance(name: &str) -> usize {
+    (name.chars().count() / 3).max(1)
+}
+
+/// Adds the names and aliases of `commands` and their subcommands, prefixed
+/// with `parent`, to `names`. Commands which cannot be invoked with a prefix,
+/// are only for owners or are hidden from help are skipped.
+fn collect_names<U, E>(commands: &[Command<U, E>], parent: &str, names: &mut Vec<String>) {
+    for command in commands {
+        if command.prefix_action.is_none() || command.owners_only || command.hide_in_help {
+            continue;
+        }
+
+        for name in std::iter::once(&command.name).chain(&command.aliases) {
+            let name = if parent.is_empty() {
+                name.clone()
+            } else {
+                format!("{parent} {name}")
+            };
+
+            collect_names(&command.subcommands, &name, names);
+            names.push(name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(name: &str, aliases: &[&str], subcommands: Vec<Command<(), ()>>) -> Command<(), ()> {
+        Command {
+            name: name.to_string(),
+            aliases: aliases.iter().map(|alias| alias.to_string()).collect(),
+            subcommands,
+            prefix_action: Some(|_| Box::pin(async { Ok(()) })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn max_distance_is_a_third_of_the_length() {
+        assert_eq!(max_distance("ab"), 1);
+        assert_eq!(max_distance("ping"), 1);
+        assert_eq!(max_distance("status"), 2);
+        assert_eq!(max_distance("leaderboard"), 3);
+    }
+
+    #[test]
+    fn only_close_enough_names_are_suggested() {
+        let commands = [
+            command("status", &[], Vec::new()),
+            command("ping", &[], Vec::new()),
+        ];
+
+        assert_eq!(similar_commands("stats", &commands), ["status"]);
+        assert_eq!(similar_commands("sttaus now", &commands), ["status"]);
+        assert_eq!(similar_commands("pnog", &commands), Vec::<String>::new());
+        assert_eq!(similar_commands("PIGN", &commands), Vec::<String>::new());
+        assert_eq!(similar_commands("Png", &commands), ["ping"]);
+        assert_eq!(similar_commands("help", &commands), Vec::<String>::new());
+    }
+
+    #[test]
+    fn subcommands_and_aliases_are_suggested() {
+        let commands = [command(
+            "shop",
+            &["store"],
+            vec![command("buy", &["purchase"], Vec::new())],
+        )];
+
+        assert_eq!(
+            similar_commands("shp buy 3", &commands),
+            ["shop", "shop buy"]
+        );
+        assert_eq!(similar_commands("stor", &commands), ["store"]);
+        assert_eq!(
+            similar_commands("stor purchse", &commands),
+            ["store", "store purchase", "shop purchase"],
+        );
+    }
+
+    #[test]
+    fn hidden_and_owner_only_commands_are_not_suggested() {
+        let mut errors = command("errors", &[], Vec::new());
+        let mut diagnose = command("diagnose", &[], Vec::new());
+        let mut slash_only = command("error", &[], Vec::new());
+
+        errors.owners_only = true;
+        diagnose.hide_in_help = true;
+        slash_only.prefix_action = None;
+
+        let commands = [errors, diagnose, slash_only];
+
+        assert!(similar_commands("eror", &commands).is_empty());
+        assert!(similar_commands("diagnse", &commands).is_empty());
+    }
+
+    #[test]
+    fn closest_uses_the_same_threshold() {
+        let choices = ["red", "green", "blue"];
+
+        assert_eq!(closest("gren", choices), Some("green"));
+        assert_eq!(closest("BLU", choices), Some("blue"));
+        assert_eq!(closest("purple", choices), None);
+    }
+}
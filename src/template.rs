@@ -0,0 +1,248 @@
+use anyhow::bail;
+use poise::{Context, serenity_prelude::Mentionable};
+
+/// A value which can be inserted into a configured message with `{name}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Placeholder {
+    Command,
+    Prefix,
+    User,
+    Remaining,
+    Permissions,
+    Subcommands,
+    IncidentId,
+}
+
+impl Placeholder {
+    const ALL: [Self; 7] = [
+        Self::Command,
+        Self::Prefix,
+        Self::User,
+        Self::Remaining,
+        Self::Permissions,
+        Self::Subcommands,
+        Self::IncidentId,
+    ];
+
+    /// Placeholders available in every message.
+    pub(crate) const CONTEXT: &[Self] = &[Self::Command, Self::Prefix, Self::User];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Command => "command",
+            Self::Prefix => "prefix",
+            Self::User => "user",
+            Self::Remaining => "remaining",
+            Self::Permissions => "permissions",
+            Self::Subcommands => "subcommands",
+            Self::IncidentId => "incident_id",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|placeholder| placeholder.name() == name)
+    }
+}
+
+/// A piece of a parsed template.
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(Placeholder),
+}
+
+/// Splits `template` into text and placeholders. `{{` and `}}` are literal
+/// braces.
+fn parse(template: &str) -> Result<Vec<Segment<'_>>, anyhow::Error> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+
+    while let Some(index) = rest.find(['{', '}']) {
+        let (text, brace) = rest.split_at(index);
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        if let Some(after) = brace.strip_prefix("{{") {
+            segments.push(Segment::Text("{"));
+            rest = after;
+        } else if let Some(after) = brace.strip_prefix("}}") {
+            segments.push(Segment::Text("}"));
+            rest = after;
+        } else if brace.starts_with('}') {
+            bail!("Unmatched `}}`, write `}}}}` for a literal brace");
+        } else {
+            let Some(end) = brace.find('}') else {
+                bail!("Unclosed `{{`, write `{{{{` for a literal brace");
+            };
+            let name = &brace[1..end];
+            let Some(placeholder) = Placeholder::from_name(name) else {
+                bail!(
+                    "Unknown placeholder `{{{name}}}`, expected one of {}",
+                    list(&Placeholder::ALL),
+                );
+            };
+
+            segments.push(Segment::Placeholder(placeholder));
+            rest = &brace[end + 1..];
+        }
+    }
+
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+
+    Ok(segments)
+}
+
+/// Formats `placeholders` as a list for error messages.
+fn list(placeholders: &[Placeholder]) -> String {
+    placeholders
+        .iter()
+        .map(|placeholder| format!("`{{{}}}`", placeholder.name()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Checks that `template` is well-formed and only uses placeholders in
+/// `available`.
+pub(crate) fn validate(template: &str, available: &[Placeholder]) -> Result<(), anyhow::Error> {
+    for segment in parse(template)? {
+        if let Segment::Placeholder(placeholder) = segment
+            && !available.contains(&placeholder)
+        {
+            bail!(
+                "`{{{}}}` is not available in this message, expected one of {}",
+                placeholder.name(),
+                list(available),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The values placeholders are replaced with when rendering a template.
+#[derive(Debug, Default)]
+pub(crate) struct Values(Vec<(Placeholder, String)>);
+
+impl Values {
+    /// Creates values with nothing filled in.
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates values for the command invoked in `ctx`.
+    pub(crate) fn of<U, E>(ctx: Context<'_, U, E>) -> Self {
+        Self::new()
+            .with(Placeholder::Command, &ctx.command().qualified_name)
+            .with(Placeholder::Prefix, ctx.prefix())
+            .with(Placeholder::User, ctx.author().mention().to_string())
+    }
+
+    /// Fills in `placeholder` with `value`.
+    pub(crate) fn with(mut self, placeholder: Placeholder, value: impl Into<String>) -> Self {
+        self.0.push((placeholder, value.into()));
+        self
+    }
+
+    fn get(&self, placeholder: Placeholder) -> &str {
+        self.0
+            .iter()
+            .rev()
+            .find(|(key, _)| *key == placeholder)
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default()
+    }
+}
+
+/// Replaces the placeholders in `template` with `values`. Placeholders without
+/// a value are removed.
+///
+/// Templates are validated when they are loaded, so a template which cannot be
+/// parsed is returned as is.
+pub(crate) fn render(template: &str, values: &Values) -> String {
+    match parse(template) {
+        Ok(segments) => segments
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text,
+                Segment::Placeholder(placeholder) => values.get(placeholder),
+            })
+            .collect(),
+        Err(_) => template.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubled_braces_are_literal() {
+        let values = Values::new().with(Placeholder::User, "@someone");
+
+        assert!(validate("{{user}} and }}{{", &[]).is_ok());
+        assert_eq!(render("{{user}} is {user}", &values), "{user} is @someone");
+        assert_eq!(render("}}{{}}", &values), "}{}");
+    }
+
+    #[test]
+    fn lone_closing_brace_is_rejected() {
+        let err = validate("oops}", &Placeholder::ALL).unwrap_err();
+
+        assert!(err.to_string().contains("Unmatched `}`"), "{err}");
+    }
+
+    #[test]
+    fn unclosed_opening_brace_is_rejected() {
+        let err = validate("try {command", &Placeholder::ALL).unwrap_err();
+
+        assert!(err.to_string().contains("Unclosed `{`"), "{err}");
+    }
+
+    #[test]
+    fn unknown_placeholders_are_rejected() {
+        let err = validate("{usr}", &Placeholder::ALL).unwrap_err();
+
+        assert!(
+            err.to_string().contains("Unknown placeholder `{usr}`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn unavailable_placeholders_are_rejected() {
+        let available = [Placeholder::Command, Placeholder::User];
+
+        assert!(validate("{user} used {command}", &available).is_ok());
+
+        let err = validate("wait {remaining}s", &available).unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("`{remaining}` is not available in this message"),
+            "{err}",
+        );
+    }
+
+    #[test]
+    fn placeholders_without_values_are_removed() {
+        let values = Values::new()
+            .with(Placeholder::Command, "buy")
+            .with(Placeholder::Command, "sell");
+
+        assert_eq!(
+            render("{command} failed{incident_id}", &values),
+            "sell failed"
+        );
+        assert_eq!(render("{user}", &Values::new()), "");
+    }
+
+    #[test]
+    fn invalid_templates_are_rendered_as_is() {
+        assert_eq!(render("oops {command", &Values::new()), "oops {command");
+    }
+}
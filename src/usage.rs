@@ -0,0 +1,121 @@
+use poise::{
+    Command, CommandParameter, Context,
+    serenity_prelude::{CommandOptionType, Mentionable, UserId},
+};
+
+use crate::message::ErrorMessage;
+
+/// Maximum number of fields in an embed.
+const MAX_FIELDS: usize = 25;
+
+/// Formats how a prefix command would be invoked with the given prefix.
+pub(crate) fn prefix_invocation(prefix: &str, bot_id: UserId, qualified_name: &str) -> String {
+    if prefix == bot_id.mention().to_string() {
+        format!("{prefix} `{qualified_name}`")
+    } else {
+        format!("`{prefix}{qualified_name}`")
+    }
+}
+
+/// Formats how `command` would be invoked in the same way as `ctx` was.
+///
+/// For slash commands, this is a clickable mention of the registered command.
+pub(crate) fn invocation<U, E>(ctx: Context<'_, U, E>, command: &Command<U, E>) -> String {
+    match ctx {
+        Context::Application(ctx) => {
+            format!("</{}:{}>", command.qualified_name, ctx.interaction.data.id)
+        }
+        Context::Prefix(_) => prefix_invocation(
+            ctx.prefix(),
+            ctx.framework().bot_id,
+            &command.qualified_name,
+        ),
+    }
+}
+
+/// Formats how `command` would be invoked in the same way as `ctx` was,
+/// followed by its parameters.
+///
+/// Required parameters are wrapped in angle brackets and optional parameters
+/// are wrapped in square brackets.
+pub(crate) fn usage<U, E>(ctx: Context<'_, U, E>, command: &Command<U, E>) -> String {
+    let invocation = invocation(ctx, command);
+    let parameters = command
+        .parameters
+        .iter()
+        .map(|parameter| {
+            if parameter.required {
+                format!("<{}>", parameter.name)
+            } else {
+                format!("[{}]", parameter.name)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if parameters.is_empty() {
+        invocation
+    } else {
+        format!("{invocation} `{parameters}`")
+    }
+}
+
+/// Returns a human-readable name for the type of `parameter`, if it is known.
+fn parameter_type<U, E>(parameter: &CommandParameter<U, E>) -> Option<&'static str> {
+    let option = serde_json::to_value(parameter.create_as_slash_command_option()?).ok()?;
+    let kind = u8::try_from(option.get("type")?.as_u64()?).ok()?;
+
+    Some(match CommandOptionType::from(kind) {
+        CommandOptionType::String => "text",
+        CommandOptionType::Integer => "whole number",
+        CommandOptionType::Boolean => "true or false",
+        CommandOptionType::User => "user",
+        CommandOptionType::Channel => "channel",
+        CommandOptionType::Role => "role",
+        CommandOptionType::Mentionable => "user or role",
+        CommandOptionType::Number => "number",
+        CommandOptionType::Attachment => "attachment",
+        _ => return None,
+    })
+}
+
+/// Adds a field showing the usage of `command`, followed by a field for each of
+/// its parameters describing whether it is required, its type or choices, and
+/// its description.
+pub(crate) fn usage_fields<U, E>(
+    ctx: Context<'_, U, E>,
+    command: &Command<U, E>,
+    mut message: ErrorMessage,
+) -> ErrorMessage {
+    message = message.field("Usage", usage(ctx, command), false);
+
+    for parameter in command.parameters.iter().take(MAX_FIELDS - 1) {
+        let name = if parameter.required {
+            format!("`{}`", parameter.name)
+        } else {
+            format!("`{}` (optional)", parameter.name)
+        };
+        let kind = if parameter.choices.is_empty() {
+            parameter_type(parameter).map(|kind| format!("Type: {kind}"))
+        } else {
+            Some(format!(
+                "Choices: {}",
+                parameter
+                    .choices
+                    .iter()
+                    .map(|choice| format!("`{}`", choice.name))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ))
+        };
+        let value = [kind, parameter.description.clone()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        message = message.field(name, value, true);
+    }
+
+    message
+}
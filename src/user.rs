@@ -0,0 +1,160 @@
+use std::fmt::Display;
+
+use crate::UserError;
+
+/// Creates a [`UserError`] from a string or format arguments, like
+/// [`anyhow::anyhow!`].
+///
+/// # Examples
+///
+/// ```
+/// use poise_error::{UserError, user_error};
+///
+/// let item = "sword";
+/// let error: UserError = user_error!("You don't have a {item}");
+///
+/// assert_eq!(error.to_string(), "You don't have a sword");
+/// ```
+#[macro_export]
+macro_rules! user_error {
+    ($($arg:tt)*) => {
+        $crate::UserError($crate::anyhow::anyhow!($($arg)*))
+    };
+}
+
+/// Returns early with a [`UserError`], like [`anyhow::bail!`].
+///
+/// # Examples
+///
+/// ```
+/// use poise_error::{anyhow, user_bail};
+///
+/// #[poise::command(prefix_command, slash_command)]
+/// async fn command(ctx: poise_error::Context<'_>) -> anyhow::Result<()> {
+///     user_bail!("You stink!")
+/// }
+/// ```
+#[macro_export]
+macro_rules! user_bail {
+    ($($arg:tt)*) => {
+        return ::core::result::Result::Err(::core::convert::From::from($crate::user_error!($($arg)*)))
+    };
+}
+
+/// Returns early with a [`UserError`] if a condition is not true, like
+/// [`anyhow::ensure!`].
+///
+/// # Examples
+///
+/// ```
+/// use poise_error::{anyhow, user_ensure};
+///
+/// #[poise::command(prefix_command, slash_command)]
+/// async fn withdraw(ctx: poise_error::Context<'_>, amount: u32) -> anyhow::Result<()> {
+///     let balance = 100;
+///
+///     user_ensure!(amount <= balance, "You only have {balance} coins");
+///
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! user_ensure {
+    ($cond:expr $(,)?) => {
+        if !$cond {
+            $crate::user_bail!(::core::concat!(
+                "Condition failed: `",
+                ::core::stringify!($cond),
+                "`",
+            ))
+        }
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::user_bail!($($arg)+)
+        }
+    };
+}
+
+/// Turns errors and missing values into [`UserError`]s, like
+/// [`anyhow::Context`].
+///
+/// # Examples
+///
+/// ```
+/// use poise_error::{UserContext, anyhow};
+///
+/// #[poise::command(prefix_command, slash_command)]
+/// async fn buy(ctx: poise_error::Context<'_>, item: String, amount: String) -> anyhow::Result<()> {
+///     let amount: u32 = amount
+///         .parse()
+///         .user_context("The amount must be a whole number")?;
+///     let price = ["sword", "shield"]
+///         .iter()
+///         .position(|name| *name == item)
+///         .with_user_context(|| format!("There is no item called {item:?}"))?;
+///
+///     Ok(())
+/// }
+/// ```
+pub trait UserContext<T> {
+    /// Turns the error or missing value into a [`UserError`], with `context`
+    /// attached.
+    fn user_context<C>(self, context: C) -> Result<T, UserError>
+    where
+        C: Display + Send + Sync + 'static;
+
+    /// Turns the error or missing value into a [`UserError`], with context
+    /// attached which is only created if there is an error.
+    fn with_user_context<C, F>(self, context: F) -> Result<T, UserError>
+    where
+        C: Display + Send + Sync + 'static,
+        F: FnOnce() -> C;
+
+    /// Turns the error or missing value into a [`UserError`] as is.
+    fn into_user_error(self) -> Result<T, UserError>;
+}
+
+impl<T, E: Into<anyhow::Error>> UserContext<T> for Result<T, E> {
+    fn user_context<C>(self, context: C) -> Result<T, UserError>
+    where
+        C: Display + Send + Sync + 'static,
+    {
+        self.map_err(|error| UserError(error.into().context(context)))
+    }
+
+    fn with_user_context<C, F>(self, context: F) -> Result<T, UserError>
+    where
+        C: Display + Send + Sync + 'static,
+        F: FnOnce() -> C,
+    {
+        self.map_err(|error| UserError(error.into().context(context())))
+    }
+
+    fn into_user_error(self) -> Result<T, UserError> {
+        self.map_err(|error| UserError(error.into()))
+    }
+}
+
+impl<T> UserContext<T> for Option<T> {
+    fn user_context<C>(self, context: C) -> Result<T, UserError>
+    where
+        C: Display + Send + Sync + 'static,
+    {
+        self.ok_or_else(|| UserError(anyhow::Error::msg(context.to_string())))
+    }
+
+    fn with_user_context<C, F>(self, context: F) -> Result<T, UserError>
+    where
+        C: Display + Send + Sync + 'static,
+        F: FnOnce() -> C,
+    {
+        self.ok_or_else(|| UserError(anyhow::Error::msg(context().to_string())))
+    }
+
+    /// Turns a missing value into a [`UserError`] saying that nothing was
+    /// found.
+    fn into_user_error(self) -> Result<T, UserError> {
+        self.user_context("Nothing was found")
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt::Write;
+
+use poise::{
+    ChoiceParameter, Context,
+    serenity_prelude::{RoleId, UserId},
+};
+
+use crate::{ErrorHandler, policy};
+
+/// How much detail about an error is shown to a user.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, ChoiceParameter)]
+pub enum Verbosity {
+    /// Only the outermost message of user errors is shown, and the details of
+    /// internal errors are hidden entirely.
+    Minimal,
+    /// The whole chain of an error is shown, without its backtrace.
+    #[default]
+    Normal,
+    /// The whole chain of an error is shown, along with its backtrace (if one
+    /// was captured) and panic payloads.
+    Verbose,
+}
+
+/// A group of users, such as those who are shown verbose errors (see
+/// [`ErrorHandler::verbose_audience`]).
+///
+/// # Examples
+///
+/// ```
+/// use poise::serenity_prelude::RoleId;
+/// use poise_error::Audience;
+///
+/// let owners_and_staff = Audience::owners().role(RoleId::new(1234));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Audience {
+    owners: bool,
+    users: Vec<UserId>,
+    roles: Vec<RoleId>,
+}
+
+impl Audience {
+    /// An audience with no one in it.
+    pub fn nobody() -> Self {
+        Self {
+            owners: false,
+            users: Vec::new(),
+            roles: Vec::new(),
+        }
+    }
+
+    /// An audience with the framework's owners in it (see
+    /// [`poise::FrameworkOptions::owners`]).
+    pub fn owners() -> Self {
+        Self {
+            owners: true,
+            ..Self::nobody()
+        }
+    }
+
+    /// Adds a user to this audience.
+    pub fn user(mut self, user_id: UserId) -> Self {
+        self.users.push(user_id);
+        self
+    }
+
+    /// Adds everyone with a role to this audience.
+    pub fn role(mut self, role_id: RoleId) -> Self {
+        self.roles.push(role_id);
+        self
+    }
+
+    /// Returns whether the author of `ctx` is in this audience.
+    pub(crate) fn contains<U, E>(&self, ctx: Context<'_, U, E>) -> bool {
+        let author_id = ctx.author().id;
+
+        if self.owners && ctx.framework().options().owners.contains(&author_id) {
+            return true;
+        }
+
+        if self.users.contains(&author_id) {
+            return true;
+        }
+
+        let roles = match ctx {
+            Context::Application(ctx) => ctx
+                .interaction
+                .member
+                .as_ref()
+                .map(|member| member.roles.as_slice()),
+            Context::Prefix(ctx) => ctx
+                .msg
+                .member
+                .as_ref()
+                .map(|member| member.roles.as_slice()),
+        };
+
+        roles.is_some_and(|roles| roles.iter().any(|role| self.roles.contains(role)))
+    }
+}
+
+impl Default for Audience {
+    /// The framework's owners.
+    fn default() -> Self {
+        Self::owners()
+    }
+}
+
+/// Returns how much detail should be shown to the author of `ctx`, according
+/// to the handler and the invoked command's
+/// [`ErrorPolicy`][crate::ErrorPolicy].
+pub(crate) fn of<U, E>(ctx: Context<'_, U, E>) -> Verbosity {
+    let handler = ErrorHandler::get();
+
+    if handler.verbose_audience.contains(ctx) {
+        Verbosity::Verbose
+    } else {
+        policy::of(ctx)
+            .verbosity
+            .unwrap_or_else(|| handler.current_verbosity())
+    }
+}
+
+/// Formats `error` with as much detail as `verbosity` allows.
+pub(crate) fn describe(error: &anyhow::Error, verbosity: Verbosity) -> String {
+    match verbosity {
+        Verbosity::Minimal => error.to_string(),
+        Verbosity::Normal => format_chain(error.chain().map(|cause| cause.to_string())),
+        Verbosity::Verbose => format!("{error:?}"),
+    }
+}
+
+/// Formats an error's chain, outermost first, in the same way as [`anyhow`]
+/// does, without a backtrace.
+pub(crate) fn format_chain(mut chain: impl Iterator<Item = String>) -> String {
+    let mut description = chain.next().unwrap_or_default();
+    let causes: Vec<String> = chain.collect();
+
+    if !causes.is_empty() {
+        description.push_str("\n\nCaused by:");
+    }
+
+    if let [cause] = causes.as_slice() {
+        write!(description, "\n    {cause}").unwrap();
+    } else {
+        for (index, cause) in causes.iter().enumerate() {
+            write!(description, "\n    {index}: {cause}").unwrap();
+        }
+    }
+
+    description
+}